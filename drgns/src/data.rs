@@ -5,9 +5,10 @@ pub enum PrimitiveValue {
     None,
     True,
     False,
-    Int(u64),
+    Int(i64),
     Float(f64),
     String(String),
+    List(Vec<PrimitiveValue>),
 }
 
 impl ToString for PrimitiveValue {
@@ -1,8 +1,10 @@
 //! Error reporting module, keeps track of all errors during compilation.
 //!
-//! Note that lints as not supported yet, these are all hard-coded errors,
-//! whereas lints can be disabled or enabled by the user. Perhaps in the
-//! future we will have lint reporting.
+//! Most diagnostics here are hard-coded: they always fire, always at
+//! [`Severity::Error`]. [`Lint`]s are the exception -- a named,
+//! independently configurable class of diagnostic (see the `lint` module)
+//! whose severity is resolved per-lint from CLI flags or a config file
+//! instead of being baked into the call site.
 //!
 //! ## Code Ranges
 //! Each error has a code, but equivalent errors of different severities,
@@ -25,7 +27,7 @@
 
 use std::{
     backtrace::Backtrace,
-    cell::Cell,
+    cell::{Cell, Ref, RefCell},
     fmt::Display,
     rc::Rc,
     sync::{
@@ -34,88 +36,297 @@ use std::{
     },
 };
 
-use ariadne::{Config, Label, Report, Source};
 use log::debug;
 use thiserror::Error;
 
 use crate::{
     lexer::{Token, TokenType},
-    source::{SourceArena, SourceView},
+    source::{FileRef, SourceArena, SourceView},
+    values::{RuntimeError, Value},
 };
 
-#[derive(Debug, Clone)]
-#[repr(u16)]
+mod emitter;
+pub use emitter::{ColorConfig, Emitter, HumanEmitter, JsonEmitter};
+
+mod lint;
+pub use lint::{Lint, LintLevel, LINTS};
+
+#[derive(Debug, Clone, Copy)]
 enum ErrorType {
-    SyntaxError = 0x1,
+    SyntaxError,
+    RuntimeError,
+    /// a [`Lint`] reported at its resolved severity; carries the lint's
+    /// code directly since it isn't known until the lint is registered
+    Lint(u16),
+}
+
+impl ErrorType {
+    fn code(self) -> u16 {
+        match self {
+            ErrorType::SyntaxError => 0x1,
+            ErrorType::RuntimeError => 0x2,
+            ErrorType::Lint(code) => code,
+        }
+    }
 }
 
-#[derive(Error, Debug)]
+/// How serious a diagnostic is, independent of its error code. Kept as a
+/// real enum rather than folded into the code range, so callers that only
+/// care about severity (e.g. "did anything fatal happen?") don't have to
+/// know the code layout documented in the module docs above.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// compiles and runs, but is likely a mistake
+    Warn,
+    /// a normal, recoverable compile/runtime error
+    Error,
+    /// the compiler itself is in a state it should never be in
+    Fatal,
+}
+
+/// A secondary label on a diagnostic: a span other than the primary one,
+/// with its own message, e.g. "previous definition was here".
+#[derive(Debug, Clone)]
+pub struct DiagnosticLabel {
+    pub span: SourceView,
+    pub msg: String,
+}
+
+#[derive(Error, Debug, Clone)]
 #[error("{msg}")]
 pub struct DragonError {
     msg: String,
     ty: ErrorType,
+    severity: Severity,
     span: Option<SourceView>,
+    /// text attached to the primary span itself, e.g. "unexpected '@'
+    /// here". Defaults to a generic pointer when a report has nothing more
+    /// specific to say.
+    label: String,
+    /// secondary labels pointing at other spans, e.g. where a name was
+    /// first defined
+    labels: Vec<DiagnosticLabel>,
+    /// rustc-style "note:" lines, for context that isn't a suggested fix
+    notes: Vec<String>,
+    /// rustc-style "help:" lines, for suggested fixes
+    help: Vec<String>,
 }
 
 impl DragonError {
-    fn report(&self, src: String) -> Result<(), std::io::Error> {
-        let mut rep = Report::build(ariadne::ReportKind::Error, (), 12)
-            .with_code(self.ty.clone() as u16)
-            .with_message(self.msg.clone());
-        if let Some(span) = self.span.clone() {
-            rep = rep.with_label(Label::new(span.span.clone()).with_message("here"));
-        } else {
-            rep = rep.with_label(Label::new(src.len()..src.len()).with_message("here"));
+    fn new(msg: impl Into<String>, ty: ErrorType, severity: Severity, span: Option<SourceView>) -> Self {
+        Self {
+            msg: msg.into(),
+            ty,
+            severity,
+            span,
+            label: "here".to_string(),
+            labels: vec![],
+            notes: vec![],
+            help: vec![],
         }
-        rep.finish().eprint(Source::from(src))
+    }
+
+    /// Override the default "here" message shown under the primary span.
+    fn with_primary_label(mut self, msg: impl Into<String>) -> Self {
+        self.label = msg.into();
+        self
+    }
+
+    /// Attach a secondary label pointing at a different span.
+    pub fn with_label(mut self, span: SourceView, msg: impl Into<String>) -> Self {
+        self.labels.push(DiagnosticLabel { span, msg: msg.into() });
+        self
+    }
+
+    pub fn with_note(mut self, text: impl Into<String>) -> Self {
+        self.notes.push(text.into());
+        self
+    }
+
+    pub fn with_help(mut self, text: impl Into<String>) -> Self {
+        self.help.push(text.into());
+        self
+    }
+
+    /// This diagnostic's numeric code, shared by every severity of the same
+    /// underlying error (see the module's "Code Ranges" docs).
+    pub fn code(&self) -> u16 {
+        self.ty.code()
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn message(&self) -> &str {
+        &self.msg
+    }
+
+    /// The primary span this diagnostic points at, if any, along with the
+    /// message attached to it.
+    pub fn primary(&self) -> (Option<&SourceView>, &str) {
+        (self.span.as_ref(), &self.label)
+    }
+
+    pub fn labels(&self) -> &[DiagnosticLabel] {
+        &self.labels
+    }
+
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    pub fn help(&self) -> &[String] {
+        &self.help
     }
 }
 
+/// Diagnostics beyond this many (after deduplication) are summarized with
+/// a trailing "... and K more" note instead of rendered individually, so
+/// one cascading parser-recovery failure doesn't flood the terminal.
+pub const DEFAULT_MAX_DIAGNOSTICS: usize = 20;
+
 pub struct ErrorHandler {
     had_error: AtomicBool,
     src: Rc<SourceArena>,
-    errors: Cell<Vec<DragonError>>,
-    warnings: Cell<Vec<DragonError>>,
+    errors: RefCell<Vec<DragonError>>,
+    warnings: RefCell<Vec<DragonError>>,
+    emitter: RefCell<Box<dyn Emitter>>,
+    lints: RefCell<lint::LintConfig>,
+    max_diagnostics: Cell<usize>,
 }
 
 impl ErrorHandler {
     pub fn new(src: &Rc<SourceArena>) -> Self {
+        Self::with_emitter(src, Box::new(HumanEmitter::default()))
+    }
+
+    /// Like [`Self::new`], but renders through `emitter` instead of the
+    /// default [`HumanEmitter`] -- e.g. a [`JsonEmitter`] for IDEs and CI
+    /// tooling that want to consume diagnostics structurally.
+    pub fn with_emitter(src: &Rc<SourceArena>, emitter: Box<dyn Emitter>) -> Self {
         Self {
             had_error: AtomicBool::new(false),
             src: src.clone(),
-            errors: Cell::new(vec![]),
-            warnings: Cell::new(vec![]),
+            errors: RefCell::new(vec![]),
+            warnings: RefCell::new(vec![]),
+            emitter: RefCell::new(emitter),
+            lints: RefCell::new(lint::LintConfig::new()),
+            max_diagnostics: Cell::new(DEFAULT_MAX_DIAGNOSTICS),
+        }
+    }
+
+    /// Cap how many of each kind of diagnostic [`Self::report_all`] renders
+    /// individually (see [`DEFAULT_MAX_DIAGNOSTICS`]). The rest are folded
+    /// into a trailing "... and K more" note.
+    pub fn set_max_diagnostics(&self, n: usize) {
+        self.max_diagnostics.set(n);
+    }
+
+    /// Override a lint's resolved level, e.g. from a `--warn`/`--deny` CLI
+    /// flag or a config file. No-op if `name` doesn't match a registered
+    /// [`Lint`].
+    pub fn set_lint_level(&self, name: &str, level: LintLevel) {
+        if let Some(l) = lint::find(name) {
+            self.lints.borrow_mut().set(l, level);
+        }
+    }
+
+    /// Report a [`Lint`] violation at `span`, after resolving its level
+    /// through any CLI/config overrides. Suppressed entirely if the
+    /// resolved level is [`LintLevel::Allow`].
+    pub fn lint(self: Rc<Self>, l: &Lint, span: SourceView, msg: impl Into<String>) {
+        let Some(severity) = self.lints.borrow().severity(l) else {
+            return;
+        };
+        let diag = DragonError::new(msg, ErrorType::Lint(l.code), severity, Some(span));
+        match severity {
+            Severity::Warn => self.warnings.borrow_mut().push(diag),
+            Severity::Error | Severity::Fatal => {
+                self.errors.borrow_mut().push(diag);
+                self.had_error.store(true, Ordering::Relaxed);
+            }
         }
     }
 
+    /// Render every collected error and warning, then print a rustc-style
+    /// "N errors, M warnings" summary line.
+    ///
+    /// Each group is normalized first (see [`normalize_diagnostics`]) and
+    /// capped at [`Self::set_max_diagnostics`]; anything past the cap is
+    /// folded into a trailing "... and K more" note rather than rendered.
     pub fn report_all(self: &Rc<Self>) {
-        let inner = self.errors.take();
-        for e in inner.iter() {
-            e.report(self.src.to_string()).unwrap();
+        let mut emitter = self.emitter.borrow_mut();
+        let cap = self.max_diagnostics.get();
+
+        let errors = normalize_diagnostics(self.errors.take());
+        let warnings = normalize_diagnostics(self.warnings.take());
+        let (err_shown, err_hidden) = split_at_cap(&errors, cap);
+        let (warn_shown, warn_hidden) = split_at_cap(&warnings, cap);
+
+        for e in err_shown.iter().chain(warn_shown) {
+            emitter.emit(e, &self.src);
+        }
+        if err_hidden > 0 {
+            eprintln!("... and {} more error{}", err_hidden, plural(err_hidden));
+        }
+        if warn_hidden > 0 {
+            eprintln!("... and {} more warning{}", warn_hidden, plural(warn_hidden));
+        }
+        if !errors.is_empty() || !warnings.is_empty() {
+            eprintln!(
+                "{} error{}, {} warning{}",
+                errors.len(),
+                plural(errors.len()),
+                warnings.len(),
+                plural(warnings.len()),
+            );
         }
     }
 
+    /// All errors collected so far (code, severity, message, labels, notes
+    /// and help), for editors or test harnesses that want to consume them
+    /// programmatically instead of scraping the rendered report. Warnings
+    /// are not included, see [`Self::warnings`].
+    pub fn diagnostics(&self) -> Ref<'_, [DragonError]> {
+        Ref::map(self.errors.borrow(), Vec::as_slice)
+    }
+
+    /// Every [`LintLevel::Warn`]-resolved diagnostic collected so far. Kept
+    /// separate from [`Self::diagnostics`] for the same reason `warnings`
+    /// and `errors` are separate fields: [`Self::report_all`] caps and
+    /// normalizes each group independently.
+    pub fn warnings(&self) -> Ref<'_, [DragonError]> {
+        Ref::map(self.warnings.borrow(), Vec::as_slice)
+    }
+
+    /// Whether any error (as opposed to a mere warning) has been reported
+    /// so far, for callers that need to decide whether to trust a result
+    /// built alongside error recovery (e.g. skip evaluating/printing a
+    /// parse tree that folded a recovered `Expression::Error` into it).
+    pub fn had_error(&self) -> bool {
+        self.had_error.load(Ordering::Relaxed)
+    }
+
     pub fn syntax_error(self: Rc<Self>, span: SourceView, msg: String) {
-        let mut errors = self.errors.take();
-        errors.push(DragonError {
-            msg,
-            ty: ErrorType::SyntaxError,
-            span: Some(span),
-        });
+        self.errors
+            .borrow_mut()
+            .push(DragonError::new(msg, ErrorType::SyntaxError, Severity::Error, Some(span)));
         self.had_error.store(true, Ordering::Relaxed);
-        self.errors.set(errors);
     }
 
     pub fn unexpected_char(self: Rc<Self>, span: SourceView, c: char) {
-        let mut errors = self.errors.take();
         log::trace!("unexpected_char");
-        errors.push(DragonError {
-            msg: format!("unexpected character: '{}'", c),
-            ty: ErrorType::SyntaxError,
-            span: Some(span),
-        });
+        self.errors.borrow_mut().push(
+            DragonError::new(
+                format!("unexpected character: '{}'", c),
+                ErrorType::SyntaxError,
+                Severity::Error,
+                Some(span),
+            )
+            .with_primary_label(format!("unexpected '{}' here", c)),
+        );
         self.had_error.store(true, Ordering::Relaxed);
-        self.errors.set(errors);
     }
 
     pub fn unexpected_token(
@@ -124,23 +335,358 @@ impl ErrorHandler {
         expected: &[TokenType],
         got: TokenType,
     ) {
-        let mut errors = self.errors.take();
-        errors.push(DragonError {
-            msg: format!("unexpected token: {}, expected one of {:?}", got, expected),
-            ty: ErrorType::SyntaxError,
-            span: Some(span),
-        });
-        self.errors.set(errors);
+        self.errors.borrow_mut().push(
+            DragonError::new(
+                format!("unexpected token: {}, expected one of {:?}", got, expected),
+                ErrorType::SyntaxError,
+                Severity::Error,
+                Some(span),
+            )
+            .with_primary_label(format!("found {} here", got))
+            .with_help(format!("expected one of {:?}", expected)),
+        );
+        self.had_error.store(true, Ordering::Relaxed);
+    }
+
+    /// Report a place where an expression was expected but none could be
+    /// parsed, e.g. the missing operand in `1 + `.
+    pub fn expect_expression(self: Rc<Self>, span: Option<SourceView>) {
+        self.errors.borrow_mut().push(DragonError::new(
+            "expected expression",
+            ErrorType::SyntaxError,
+            Severity::Error,
+            span,
+        ));
+        self.had_error.store(true, Ordering::Relaxed);
+    }
+
+    /// Report an out-of-bounds list index. There is no span to attach here
+    /// since the evaluator's stack machine doesn't track the source
+    /// location of a `Value` once it is pushed.
+    pub fn index_out_of_bounds(self: Rc<Self>, len: usize, index: Value) {
+        self.errors.borrow_mut().push(
+            DragonError::new(
+                format!("index out of bounds: length is {} but index was {}", len, index),
+                ErrorType::RuntimeError,
+                Severity::Error,
+                None,
+            )
+            .with_help(format!("valid indices are 0..{}", len)),
+        );
+        self.had_error.store(true, Ordering::Relaxed);
+    }
+
+    /// Report a [`RuntimeError`] raised by `Value` arithmetic. There is no
+    /// span here for the same reason as [`Self::index_out_of_bounds`]: the
+    /// stack machine has already discarded the operands' source locations
+    /// by the time the error surfaces.
+    pub fn runtime_error(self: Rc<Self>, err: RuntimeError) {
+        let mut diag = DragonError::new(err.to_string(), ErrorType::RuntimeError, Severity::Error, None);
+        if matches!(err, RuntimeError::IntegerOverflow) {
+            diag = diag
+                .with_help("use the wrapping or saturating overflow policy if this is intentional");
+        }
+        self.errors.borrow_mut().push(diag);
+        self.had_error.store(true, Ordering::Relaxed);
     }
 
     pub fn unexpected_end_of_input(self: Rc<Self>) {
-        let mut errors = self.errors.take();
-        errors.push(DragonError {
-            msg: format!("unexpected end of input"),
-            ty: ErrorType::SyntaxError,
-            span: None,
+        self.errors.borrow_mut().push(DragonError::new(
+            "unexpected end of input",
+            ErrorType::SyntaxError,
+            Severity::Error,
+            None,
+        ));
+        self.had_error.store(true, Ordering::Relaxed);
+    }
+
+    /// Report a string literal that reaches end of input before its closing `"`.
+    pub fn unterminated_string(self: Rc<Self>, span: SourceView) {
+        self.errors.borrow_mut().push(
+            DragonError::new(
+                "unterminated string literal",
+                ErrorType::SyntaxError,
+                Severity::Error,
+                Some(span),
+            )
+            .with_primary_label("string starts here")
+            .with_help("add a closing '\"'"),
+        );
+        self.had_error.store(true, Ordering::Relaxed);
+    }
+
+    /// Report a radix-prefixed integer literal (`0x`, `0o`, `0b`) with no
+    /// digits of the right class following the prefix.
+    pub fn bad_number_literal(self: Rc<Self>, span: SourceView, prefix: char) {
+        self.errors.borrow_mut().push(
+            DragonError::new(
+                format!("expected digits after '0{}'", prefix),
+                ErrorType::SyntaxError,
+                Severity::Error,
+                Some(span),
+            )
+            .with_primary_label("expected digits here")
+            .with_help(format!("'0{}' must be followed by at least one digit", prefix)),
+        );
+        self.had_error.store(true, Ordering::Relaxed);
+    }
+
+    /// Report a `/* ... */` block comment (possibly nested) that reaches
+    /// end of input before every opened `/*` has a matching `*/`.
+    pub fn unterminated_block_comment(self: Rc<Self>, span: SourceView) {
+        self.errors.borrow_mut().push(
+            DragonError::new(
+                "unterminated block comment",
+                ErrorType::SyntaxError,
+                Severity::Error,
+                Some(span),
+            )
+            .with_primary_label("comment starts here")
+            .with_help("add a closing '*/'"),
+        );
+        self.had_error.store(true, Ordering::Relaxed);
+    }
+
+    /// Report a `\` escape inside a string that isn't one of the recognized
+    /// forms (`\n`, `\t`, `\r`, `\\`, `\"`, `\$`, `\xHH`, `\u{...}`).
+    pub fn bad_escape(self: Rc<Self>, span: SourceView, c: char) {
+        self.errors.borrow_mut().push(
+            DragonError::new(
+                format!("invalid escape sequence: '\\{}'", c),
+                ErrorType::SyntaxError,
+                Severity::Error,
+                Some(span),
+            )
+            .with_primary_label(format!("'\\{}' is not a recognized escape", c))
+            .with_help(r#"recognized escapes are \n, \t, \r, \\, \", \$, \xHH, \u{...}"#),
+        );
+        self.had_error.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_LINT: Lint = Lint {
+        name: "test-lint",
+        code: 0xF00,
+        default_level: LintLevel::Warn,
+    };
+
+    fn new_handler() -> Rc<ErrorHandler> {
+        let arena = Rc::new(SourceArena::new());
+        arena.intern(String::new());
+        Rc::new(ErrorHandler::new(&arena))
+    }
+
+    #[test]
+    fn allow_override_suppresses_the_lint_entirely() {
+        let eh = new_handler();
+        eh.set_lint_level(TEST_LINT.name, LintLevel::Allow);
+        let span = SourceView::from_arena(&eh.src.clone());
+        eh.clone().lint(&TEST_LINT, span, "should be suppressed");
+        assert!(eh.diagnostics().is_empty());
+        assert!(eh.warnings().is_empty());
+    }
+
+    #[test]
+    fn default_level_reports_as_a_warning() {
+        let eh = new_handler();
+        let span = SourceView::from_arena(&eh.src.clone());
+        eh.clone().lint(&TEST_LINT, span, "a warning");
+        assert_eq!(eh.warnings().len(), 1);
+        assert!(eh.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn deny_override_reports_as_an_error() {
+        let eh = new_handler();
+        eh.set_lint_level(TEST_LINT.name, LintLevel::Deny);
+        let span = SourceView::from_arena(&eh.src.clone());
+        eh.clone().lint(&TEST_LINT, span, "an error");
+        assert_eq!(eh.diagnostics().len(), 1);
+        assert!(eh.warnings().is_empty());
+    }
+
+    #[test]
+    fn forbid_default_cannot_be_allowed_away() {
+        let forbid_lint = Lint {
+            name: "test-forbid-lint",
+            code: 0xF01,
+            default_level: LintLevel::Forbid,
+        };
+        let eh = new_handler();
+        eh.set_lint_level(forbid_lint.name, LintLevel::Allow);
+        let span = SourceView::from_arena(&eh.src.clone());
+        eh.clone().lint(&forbid_lint, span, "still an error");
+        assert_eq!(eh.diagnostics().len(), 1);
+    }
+
+    #[test]
+    fn set_lint_level_on_an_unknown_name_is_a_no_op() {
+        let eh = new_handler();
+        eh.set_lint_level("not-a-real-lint", LintLevel::Deny);
+        let span = SourceView::from_arena(&eh.src.clone());
+        eh.clone().lint(&TEST_LINT, span, "still a warning");
+        assert_eq!(eh.warnings().len(), 1);
+    }
+
+    /// Build a diagnostic with an arbitrary code and span, for exercising
+    /// `normalize_diagnostics`/`split_at_cap` without a real parse/eval run.
+    fn diag_at(code: u16, start: usize, end: usize, msg: &str) -> DragonError {
+        let span = SourceView {
+            arena: std::rc::Weak::new(),
+            span: start..end,
+            source_id: 0,
+        };
+        DragonError::new(msg, ErrorType::Lint(code), Severity::Error, Some(span))
+    }
+
+    /// Like [`diag_at`], but with no span, the shape the stack-machine
+    /// evaluator's errors take.
+    fn diag_spanless(code: u16, msg: &str) -> DragonError {
+        DragonError::new(msg, ErrorType::Lint(code), Severity::Error, None)
+    }
+
+    #[test]
+    fn normalize_diagnostics_sorts_by_span_start() {
+        let diags = vec![diag_at(0x10, 10, 12, "second"), diag_at(0x10, 0, 2, "first")];
+        let out = normalize_diagnostics(diags);
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[0].message(), "first");
+        assert_eq!(out[1].message(), "second");
+    }
+
+    #[test]
+    fn normalize_diagnostics_collapses_overlapping_same_code_spans() {
+        let diags = vec![diag_at(0x10, 0, 5, "first report"), diag_at(0x10, 3, 8, "cascaded report")];
+        let out = normalize_diagnostics(diags);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].message(), "first report");
+    }
+
+    #[test]
+    fn normalize_diagnostics_keeps_non_overlapping_same_code_spans() {
+        let diags = vec![diag_at(0x10, 0, 2, "first"), diag_at(0x10, 10, 12, "second")];
+        let out = normalize_diagnostics(diags);
+        assert_eq!(out.len(), 2);
+    }
+
+    #[test]
+    fn normalize_diagnostics_does_not_collapse_different_codes_at_the_same_span() {
+        let diags = vec![diag_at(0x10, 0, 5, "a"), diag_at(0x11, 0, 5, "b")];
+        let out = normalize_diagnostics(diags);
+        assert_eq!(out.len(), 2);
+    }
+
+    #[test]
+    fn normalize_diagnostics_collapses_identical_spanless_diagnostics() {
+        let diags = vec![diag_spanless(0x10, "same message"), diag_spanless(0x10, "same message")];
+        let out = normalize_diagnostics(diags);
+        assert_eq!(out.len(), 1);
+    }
+
+    #[test]
+    fn normalize_diagnostics_keeps_distinct_spanless_diagnostics() {
+        let diags = vec![diag_spanless(0x10, "one"), diag_spanless(0x10, "two")];
+        let out = normalize_diagnostics(diags);
+        assert_eq!(out.len(), 2);
+    }
+
+    #[test]
+    fn split_at_cap_returns_everything_when_under_the_cap() {
+        let diags = vec![diag_spanless(0x10, "one"), diag_spanless(0x10, "two")];
+        let (shown, hidden) = split_at_cap(&diags, 5);
+        assert_eq!(shown.len(), 2);
+        assert_eq!(hidden, 0);
+    }
+
+    #[test]
+    fn split_at_cap_returns_everything_when_exactly_at_the_cap() {
+        let diags = vec![diag_spanless(0x10, "one"), diag_spanless(0x10, "two")];
+        let (shown, hidden) = split_at_cap(&diags, 2);
+        assert_eq!(shown.len(), 2);
+        assert_eq!(hidden, 0);
+    }
+
+    #[test]
+    fn split_at_cap_truncates_and_counts_the_rest_when_over_the_cap() {
+        let diags = vec![
+            diag_spanless(0x10, "one"),
+            diag_spanless(0x10, "two"),
+            diag_spanless(0x10, "three"),
+        ];
+        let (shown, hidden) = split_at_cap(&diags, 1);
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].message(), "one");
+        assert_eq!(hidden, 2);
+    }
+
+    #[test]
+    fn split_at_cap_of_an_empty_slice_is_empty() {
+        let diags: Vec<DragonError> = vec![];
+        let (shown, hidden) = split_at_cap(&diags, 5);
+        assert!(shown.is_empty());
+        assert_eq!(hidden, 0);
+    }
+}
+
+fn plural(n: usize) -> &'static str {
+    if n == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+/// `(file, start, end)` for a diagnostic's primary span, or `None` if it
+/// has none (e.g. the stack-machine errors that can't recover a location).
+fn span_key(diag: &DragonError) -> Option<(FileRef, usize, usize)> {
+    diag.primary().0.map(|s| (s.file(), s.start(), s.end()))
+}
+
+/// Sort diagnostics by `(source_id, span start, code)` and collapse
+/// exact duplicates and adjacent same-code diagnostics whose spans
+/// overlap into a single entry -- the shape a parser-recovery cascade
+/// takes when one malformed region produces many near-identical errors.
+/// Diagnostics with no span (e.g. from the stack-machine evaluator) sort
+/// after every spanned one, ordered by code and keeping their relative
+/// order within a code; two adjacent ones collapse only when their code
+/// and message are both identical.
+fn normalize_diagnostics(mut diags: Vec<DragonError>) -> Vec<DragonError> {
+    diags.sort_by_key(|d| {
+        let source_id = span_key(d).map(|(f, ..)| f.id()).unwrap_or(u16::MAX);
+        let start = span_key(d).map(|(_, s, _)| s).unwrap_or(usize::MAX);
+        (source_id, start, d.code())
+    });
+
+    let mut out: Vec<DragonError> = Vec::with_capacity(diags.len());
+    for diag in diags {
+        let collapses_into_prev = out.last().is_some_and(|prev: &DragonError| {
+            if prev.code() != diag.code() {
+                return false;
+            }
+            match (span_key(prev), span_key(&diag)) {
+                (Some((fa, sa, ea)), Some((fb, sb, eb))) => fa == fb && sa <= eb && sb <= ea,
+                (None, None) => prev.message() == diag.message(),
+                _ => false,
+            }
         });
-        self.errors.set(errors);
+        if !collapses_into_prev {
+            out.push(diag);
+        }
+    }
+    out
+}
+
+/// Split `diags` into the slice [`report_all`](ErrorHandler::report_all)
+/// should render and the count of diagnostics past the cap.
+fn split_at_cap(diags: &[DragonError], cap: usize) -> (&[DragonError], usize) {
+    if diags.len() <= cap {
+        (diags, 0)
+    } else {
+        (&diags[..cap], diags.len() - cap)
     }
 }
 
@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+
+use ariadne::{Config, Label, Report};
+
+use crate::source::{FileRef, SourceArena, SourceView};
+
+use super::{DragonError, Severity};
+
+/// How a [`HumanEmitter`] should color its output. `Auto` is almost always
+/// the right choice -- it's only a separate option so tests and non-TTY
+/// pipelines (CI logs, output piped to a file) can force it off or on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorConfig {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorConfig {
+    fn resolve(self) -> bool {
+        match self {
+            ColorConfig::Always => true,
+            ColorConfig::Never => false,
+            ColorConfig::Auto => std::io::IsTerminal::is_terminal(&std::io::stderr()),
+        }
+    }
+}
+
+/// Renders a [`DragonError`] somewhere -- a terminal, a JSON stream, a log
+/// file. [`super::ErrorHandler::report_all`] dispatches through whichever
+/// emitter it was constructed with, instead of being hard-wired to one
+/// rendering.
+pub trait Emitter {
+    fn emit(&mut self, diag: &DragonError, src: &SourceArena);
+}
+
+/// rustc/ariadne-style rendering with underlined source snippets, notes and
+/// help lines, for a human reading a terminal. The default emitter.
+pub struct HumanEmitter {
+    color: ColorConfig,
+}
+
+impl HumanEmitter {
+    pub fn new(color: ColorConfig) -> Self {
+        Self { color }
+    }
+}
+
+impl Default for HumanEmitter {
+    fn default() -> Self {
+        Self::new(ColorConfig::default())
+    }
+}
+
+impl Emitter for HumanEmitter {
+    fn emit(&mut self, diag: &DragonError, src: &SourceArena) {
+        let kind = match diag.severity() {
+            Severity::Warn => ariadne::ReportKind::Warning,
+            Severity::Error | Severity::Fatal => ariadne::ReportKind::Error,
+        };
+
+        let (primary, label) = diag.primary();
+        let primary_file = primary.map(SourceView::file);
+        let primary_name = primary_file
+            .map(|f| file_name(src, f))
+            .unwrap_or_else(|| "<unknown>".to_string());
+        let primary_span = primary
+            .map(|s| src.local_span(s.file(), s.span.clone()))
+            .unwrap_or(0..0);
+
+        let mut rep = Report::build(kind, primary_name.clone(), primary_span.start)
+            .with_config(Config::default().with_color(self.color.resolve()))
+            .with_code(diag.code())
+            .with_message(diag.message());
+
+        rep = rep.with_label(Label::new((primary_name, primary_span)).with_message(label));
+        for l in diag.labels() {
+            let name = file_name(src, l.span.file());
+            let span = src.local_span(l.span.file(), l.span.span.clone());
+            rep = rep.with_label(Label::new((name, span)).with_message(l.msg.clone()));
+        }
+        for note in diag.notes() {
+            rep = rep.with_note(note.clone());
+        }
+        for help in diag.help() {
+            rep = rep.with_help(help.clone());
+        }
+
+        // Only the files actually referenced by this diagnostic's spans
+        // need to be fed to ariadne -- not the whole arena.
+        let mut files: HashSet<FileRef> = primary_file.into_iter().collect();
+        files.extend(diag.labels().iter().map(|l| l.span.file()));
+        let sources = files
+            .into_iter()
+            .map(|f| (file_name(src, f), src.file_text(f)));
+
+        rep.finish().eprint(ariadne::sources(sources)).unwrap();
+    }
+}
+
+/// Resolve a [`FileRef`] to the name `ariadne` should print in a report
+/// header, falling back to a synthetic name for ids that (shouldn't, but
+/// could) go unregistered.
+fn file_name(src: &SourceArena, file: FileRef) -> String {
+    src.filename(file)
+        .unwrap_or_else(|| format!("<source {}>", file))
+}
+
+/// One JSON object per line: code, severity, message, and the diagnostic's
+/// spans (primary first, then any secondary labels), each as
+/// `{file, start, end, label}`. Lets IDEs and CI tooling consume
+/// dragon-script errors structurally instead of parsing rendered text.
+#[derive(Default)]
+pub struct JsonEmitter;
+
+impl Emitter for JsonEmitter {
+    fn emit(&mut self, diag: &DragonError, src: &SourceArena) {
+        let severity = match diag.severity() {
+            Severity::Warn => "warn",
+            Severity::Error => "error",
+            Severity::Fatal => "fatal",
+        };
+
+        let (primary, primary_label) = diag.primary();
+        let mut spans: Vec<String> = primary
+            .into_iter()
+            .map(|s| span_json(s, primary_label, src))
+            .collect();
+        spans.extend(diag.labels().iter().map(|l| span_json(&l.span, &l.msg, src)));
+
+        let notes = join_json_strings(diag.notes());
+        let help = join_json_strings(diag.help());
+
+        println!(
+            "{{\"code\":{},\"severity\":\"{}\",\"message\":\"{}\",\"spans\":[{}],\"notes\":[{}],\"help\":[{}]}}",
+            diag.code(),
+            severity,
+            json_escape(diag.message()),
+            spans.join(","),
+            notes,
+            help,
+        );
+    }
+}
+
+fn span_json(view: &SourceView, label: &str, src: &SourceArena) -> String {
+    let file = file_name(src, view.file());
+    let local = src.local_span(view.file(), view.span.clone());
+    format!(
+        "{{\"file\":\"{}\",\"start\":{},\"end\":{},\"label\":\"{}\"}}",
+        json_escape(&file),
+        local.start,
+        local.end,
+        json_escape(label),
+    )
+}
+
+fn join_json_strings(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|s| format!("\"{}\"", json_escape(s)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Minimal JSON string escaping: the control characters and quote/backslash
+/// that would otherwise break a `"..."` literal. Source text is assumed to
+/// already be valid Rust `char`s, so no further validation is needed.
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn json_escape_handles_quotes_and_backslashes() {
+        assert_eq!(json_escape(r#"say "hi" \ ok"#), r#"say \"hi\" \\ ok"#);
+    }
+
+    #[test]
+    fn json_escape_handles_control_characters() {
+        assert_eq!(json_escape("a\nb\tc\rd"), "a\\nb\\tc\\rd");
+        assert_eq!(json_escape("\u{1}"), "\\u0001");
+    }
+
+    #[test]
+    fn json_escape_leaves_ordinary_text_untouched() {
+        assert_eq!(json_escape("hello world"), "hello world");
+    }
+
+    #[test]
+    fn join_json_strings_escapes_and_joins_each_item() {
+        let items = vec!["a".to_string(), "b\"c".to_string()];
+        assert_eq!(join_json_strings(&items), "\"a\",\"b\\\"c\"");
+    }
+
+    #[test]
+    fn join_json_strings_of_empty_slice_is_empty() {
+        assert_eq!(join_json_strings(&[]), "");
+    }
+
+    #[test]
+    fn span_json_renders_file_offsets_and_label() {
+        let arena = Rc::new(SourceArena::new());
+        let view = arena.intern_file("test.ds".to_string(), "1 + 2".to_string());
+        let span = SourceView {
+            arena: view.arena.clone(),
+            span: 2..3,
+            source_id: view.source_id,
+        };
+        let json = span_json(&span, "here", &arena);
+        assert_eq!(json, r#"{"file":"test.ds","start":2,"end":3,"label":"here"}"#);
+    }
+}
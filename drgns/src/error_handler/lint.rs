@@ -0,0 +1,157 @@
+//! Lints: a named, independently configurable class of diagnostic.
+//!
+//! Unlike the errors in the parent module -- which always fire, always at
+//! a severity baked into the call site -- each [`Lint`] has a *default*
+//! [`LintLevel`] that the user can override per-lint from CLI flags or a
+//! config file. A lint's numeric code is shared with whatever hard-coded
+//! error it stands in for, per the parent module's "equivalent errors of
+//! different severities share the same code" rule.
+
+use std::collections::HashMap;
+
+/// How seriously a [`Lint`] should be taken. Ordered from least to most
+/// restrictive so a `Forbid` default can refuse to be overridden by
+/// anything weaker than itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintLevel {
+    /// never reported
+    Allow,
+    /// reported as a warning
+    Warn,
+    /// reported as an error
+    Deny,
+    /// reported as an error; cannot be downgraded by an override
+    Forbid,
+}
+
+impl LintLevel {
+    /// The [`Severity`](super::Severity) this level reports at, or `None`
+    /// if the lint is silenced entirely.
+    fn severity(self) -> Option<super::Severity> {
+        match self {
+            LintLevel::Allow => None,
+            LintLevel::Warn => Some(super::Severity::Warn),
+            LintLevel::Deny | LintLevel::Forbid => Some(super::Severity::Error),
+        }
+    }
+}
+
+/// A named, independently configurable diagnostic, e.g. `unused-variable`.
+/// `code` matches the [`ErrorType`](super::ErrorType) of the equivalent
+/// hard-coded error, since a lint is really that same error family
+/// reported at a user-chosen severity instead of a fixed one.
+#[derive(Debug, Clone, Copy)]
+pub struct Lint {
+    pub name: &'static str,
+    pub code: u16,
+    pub default_level: LintLevel,
+}
+
+/// The set of lints dragon-script knows about. New lints are added here
+/// rather than constructed ad hoc at the call site, so overrides and
+/// `--help`-style listings have one place to enumerate them from.
+pub static LINTS: &[Lint] = &[Lint {
+    name: "unused-variable",
+    // shares RuntimeError's code range until semantic analysis gets its
+    // own range in the module docs' "Code Ranges" table
+    code: 0x2,
+    default_level: LintLevel::Warn,
+}];
+
+/// Look up a known lint by its stable name, e.g. for resolving a
+/// `--deny unused-variable` CLI flag.
+pub fn find(name: &str) -> Option<&'static Lint> {
+    LINTS.iter().find(|l| l.name == name)
+}
+
+/// Per-lint level overrides, layered on top of each [`Lint`]'s
+/// `default_level`.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    overrides: HashMap<&'static str, LintLevel>,
+}
+
+impl LintConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override `lint`'s resolved level. A `Forbid` default can never be
+    /// downgraded: the override is silently dropped rather than letting
+    /// a stray `--allow` or config file defeat a lint the maintainer
+    /// decided should always be an error.
+    pub fn set(&mut self, lint: &Lint, level: LintLevel) {
+        if lint.default_level == LintLevel::Forbid && level < LintLevel::Forbid {
+            return;
+        }
+        self.overrides.insert(lint.name, level);
+    }
+
+    /// `lint`'s resolved [`Severity`](super::Severity), or `None` if it
+    /// resolves to [`LintLevel::Allow`].
+    pub fn severity(&self, lint: &Lint) -> Option<super::Severity> {
+        self.overrides
+            .get(lint.name)
+            .copied()
+            .unwrap_or(lint.default_level)
+            .severity()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::Severity;
+
+    const WARN_LINT: Lint = Lint {
+        name: "test-warn-lint",
+        code: 0xF00,
+        default_level: LintLevel::Warn,
+    };
+
+    const FORBID_LINT: Lint = Lint {
+        name: "test-forbid-lint",
+        code: 0xF01,
+        default_level: LintLevel::Forbid,
+    };
+
+    #[test]
+    fn default_level_is_used_with_no_override() {
+        let cfg = LintConfig::new();
+        assert_eq!(cfg.severity(&WARN_LINT), Some(Severity::Warn));
+    }
+
+    #[test]
+    fn allow_silences_the_lint() {
+        let mut cfg = LintConfig::new();
+        cfg.set(&WARN_LINT, LintLevel::Allow);
+        assert_eq!(cfg.severity(&WARN_LINT), None);
+    }
+
+    #[test]
+    fn deny_override_reports_as_error() {
+        let mut cfg = LintConfig::new();
+        cfg.set(&WARN_LINT, LintLevel::Deny);
+        assert_eq!(cfg.severity(&WARN_LINT), Some(Severity::Error));
+    }
+
+    #[test]
+    fn forbid_default_cannot_be_downgraded() {
+        let mut cfg = LintConfig::new();
+        cfg.set(&FORBID_LINT, LintLevel::Allow);
+        assert_eq!(cfg.severity(&FORBID_LINT), Some(Severity::Error));
+    }
+
+    #[test]
+    fn forbid_default_can_be_reasserted() {
+        let mut cfg = LintConfig::new();
+        cfg.set(&FORBID_LINT, LintLevel::Forbid);
+        assert_eq!(cfg.severity(&FORBID_LINT), Some(Severity::Error));
+    }
+
+    #[test]
+    fn find_looks_up_a_registered_lint_by_name() {
+        assert!(find("unused-variable").is_some());
+        assert!(find("not-a-real-lint").is_none());
+    }
+}
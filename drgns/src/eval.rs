@@ -1,14 +1,36 @@
+use std::rc::Rc;
+
 use crate::{
+    error_handler::ErrorHandler,
     parser::{LitExpression, Visitor},
-    values::Value,
+    values::{OverflowPolicy, RuntimeError, Value},
 };
 
-#[derive(Debug)]
-pub struct ExpressionEval(Vec<Value>);
+pub struct ExpressionEval {
+    stack: Vec<Value>,
+    eh: Rc<ErrorHandler>,
+    policy: OverflowPolicy,
+}
 
 impl ExpressionEval {
-    pub fn new() -> Self {
-        Self(vec![])
+    pub fn new(eh: &Rc<ErrorHandler>, policy: OverflowPolicy) -> Self {
+        Self {
+            stack: vec![],
+            eh: eh.clone(),
+            policy,
+        }
+    }
+
+    /// the value left on top of the stack once the tree has been fully walked
+    pub fn result(&self) -> Option<&Value> {
+        self.stack.last()
+    }
+
+    /// report a `Value` arithmetic failure and push a placeholder so
+    /// evaluation can keep walking the rest of the tree
+    fn failed(&mut self, err: RuntimeError) -> Value {
+        self.eh.clone().runtime_error(err);
+        Value::Int(0)
     }
 }
 
@@ -20,34 +42,87 @@ impl Visitor<()> for ExpressionEval {
     fn visit_bin_operator(&mut self, bo: &crate::parser::BinOperator) {
         match bo {
             crate::parser::BinOperator::Pow => {
-                let x = self.0.pop().unwrap();
-                let y = self.0.pop().unwrap();
-                self.0.push(x.pow(y).unwrap());
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                let v = x.pow(y, self.policy).unwrap_or_else(|e| self.failed(e));
+                self.stack.push(v);
             }
             crate::parser::BinOperator::Mul => {
-                let x = self.0.pop().unwrap();
-                let y = self.0.pop().unwrap();
-                self.0.push(x.mul(y).unwrap());
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                let v = x.mul(y, self.policy).unwrap_or_else(|e| self.failed(e));
+                self.stack.push(v);
             }
             crate::parser::BinOperator::Div => {
-                let x = self.0.pop().unwrap();
-                let y = self.0.pop().unwrap();
-                self.0.push(x.div(y).unwrap());
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                let v = x.div(y, self.policy).unwrap_or_else(|e| self.failed(e));
+                self.stack.push(v);
             }
             crate::parser::BinOperator::Mod => {
-                let x = self.0.pop().unwrap();
-                let y = self.0.pop().unwrap();
-                self.0.push(x.rem(y).unwrap());
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                let v = x.rem(y, self.policy).unwrap_or_else(|e| self.failed(e));
+                self.stack.push(v);
             }
             crate::parser::BinOperator::Add => {
-                let x = self.0.pop().unwrap();
-                let y = self.0.pop().unwrap();
-                self.0.push(x.add(y).unwrap());
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                let v = x.add(y, self.policy).unwrap_or_else(|e| self.failed(e));
+                self.stack.push(v);
             }
             crate::parser::BinOperator::Sub => {
-                let x = self.0.pop().unwrap();
-                let y = self.0.pop().unwrap();
-                self.0.push(x.sub(y).unwrap());
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                let v = x.sub(y, self.policy).unwrap_or_else(|e| self.failed(e));
+                self.stack.push(v);
+            }
+            crate::parser::BinOperator::Lt => {
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                let v = x.lt(y).unwrap_or_else(|e| self.failed(e));
+                self.stack.push(v);
+            }
+            crate::parser::BinOperator::Le => {
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                let v = x.le(y).unwrap_or_else(|e| self.failed(e));
+                self.stack.push(v);
+            }
+            crate::parser::BinOperator::Gt => {
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                let v = x.gt(y).unwrap_or_else(|e| self.failed(e));
+                self.stack.push(v);
+            }
+            crate::parser::BinOperator::Ge => {
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                let v = x.ge(y).unwrap_or_else(|e| self.failed(e));
+                self.stack.push(v);
+            }
+            crate::parser::BinOperator::Eq => {
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                self.stack.push(Value::Bool(x.eq(&y)));
+            }
+            crate::parser::BinOperator::Ne => {
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                self.stack.push(Value::Bool(x.ne(&y)));
+            }
+            crate::parser::BinOperator::Index => {
+                let x = self.stack.pop().unwrap();
+                let y = self.stack.pop().unwrap();
+                let v = match x.index(y.clone()) {
+                    Ok(v) => v,
+                    Err(RuntimeError::IndexOutOfBounds { len, .. }) => {
+                        self.eh.clone().index_out_of_bounds(len, y);
+                        Value::Int(0)
+                    }
+                    Err(e) => self.failed(e),
+                };
+                self.stack.push(v);
             }
         }
     }
@@ -57,15 +132,81 @@ impl Visitor<()> for ExpressionEval {
     fn visit_un_operator(&mut self, uo: &crate::parser::UnOperator) {
         match uo {
             crate::parser::UnOperator::Neg => {
-                let x = self.0.pop().unwrap();
-                self.0.push(x.neg().unwrap());
+                let x = self.stack.pop().unwrap();
+                let v = x.neg(self.policy).unwrap_or_else(|e| self.failed(e));
+                self.stack.push(v);
+            }
+            crate::parser::UnOperator::Not => {
+                let x = self.stack.pop().unwrap();
+                let v = x.not().unwrap_or_else(|e| self.failed(e));
+                self.stack.push(v);
             }
         }
     }
 
     fn visit_lit_expression(&mut self, le: &crate::parser::LitExpression) {
         match le {
-            LitExpression(v) => self.0.push(v.clone()),
+            LitExpression(v) => self.stack.push(v.item.clone()),
         }
     }
+
+    /// Every item was already walked (in reverse, see `ListExpression::walk`),
+    /// so `items.len()` pops in order reconstruct the list as written.
+    fn visit_list_expression(&mut self, lse: &crate::parser::ListExpression) {
+        let items = (0..lse.items.len())
+            .map(|_| self.stack.pop().unwrap())
+            .collect();
+        self.stack.push(Value::list(items));
+    }
+
+    fn visit_index_assign_expression(&mut self, _iae: &crate::parser::IndexAssignExpression) {
+        let target = self.stack.pop().unwrap();
+        let index = self.stack.pop().unwrap();
+        let value = self.stack.pop().unwrap();
+        let v = match target.index_assign(index, value) {
+            Ok(v) => v,
+            Err(RuntimeError::IndexOutOfBounds { len, index }) => {
+                self.eh.clone().index_out_of_bounds(len, Value::Int(index));
+                Value::Int(0)
+            }
+            Err(e) => self.failed(e),
+        };
+        self.stack.push(v);
+    }
+
+    /// `&&`/`||` short-circuit: `rhs` is only walked (and thus only
+    /// evaluated, with whatever side effects that entails) if `lhs` doesn't
+    /// already determine the result.
+    fn visit_logic_expression(&mut self, le: &crate::parser::LogicExpression) {
+        le.lhs.walk(self);
+        let lhs = self.stack.pop().unwrap();
+        let lhs_truth = lhs.truthy().unwrap_or_else(|e| {
+            self.failed(e);
+            false
+        });
+
+        let short_circuit = match le.op {
+            crate::parser::LogicOperator::And if !lhs_truth => Some(false),
+            crate::parser::LogicOperator::Or if lhs_truth => Some(true),
+            _ => None,
+        };
+        if let Some(result) = short_circuit {
+            self.stack.push(Value::Bool(result));
+            return;
+        }
+
+        le.rhs.walk(self);
+        let rhs = self.stack.pop().unwrap();
+        let rhs_truth = rhs.truthy().unwrap_or_else(|e| {
+            self.failed(e);
+            false
+        });
+        self.stack.push(Value::Bool(rhs_truth));
+    }
+
+    /// the parser already reported the syntax error this placeholder stands
+    /// in for, so just push an inert value and let evaluation carry on
+    fn visit_error_expression(&mut self) {
+        self.stack.push(Value::Int(0));
+    }
 }
@@ -13,7 +13,8 @@ use crate::{
     assert_unreachable,
     eh::ErrorHandler,
     error_handler as eh, internal_error,
-    source::{Reader, SourceView},
+    lookahead::Cursor,
+    source::{SourceCursor, SourceView},
 };
 
 #[cfg(test)]
@@ -22,12 +23,17 @@ mod test_utils;
 #[cfg(test)]
 mod test;
 
+mod asi;
+pub use asi::Asi;
+
 #[derive(Debug, Clone, Copy, PartialEq, EnumIter)]
 pub enum TokenType {
     // unambiguously single-character tokens
     Semicolon,
     LeftParen,
     RightParen,
+    LeftBracket,
+    RightBracket,
     Comma,
     Plus,
     Percent,
@@ -37,19 +43,54 @@ pub enum TokenType {
     Slash, // or comment
     Star,
     Pow,
+    Bang,
+    BangEquals,
+    Lt,
+    LtEquals,
+    Gt,
+    GtEquals,
+    /// `=`, the index-assignment operator, e.g. `[1, 2][0] = 3`
+    Equals,
 
     // two character
     ColonEquals,
+    EqualsEquals,
+    AmpAmp,
+    PipePipe,
+
+    // braces, only meaningful inside a `${ ... }` interpolation for now, see
+    // `LexerMode::Interpolation`
+    LeftBrace,
+    RightBrace,
 
     // literals
     Identifier,
     IntLit,
+    FloatLit,
+    /// a decimal or float literal with a trailing `i`, e.g. `3i`, `2.0i`
+    ImaginaryLit,
+
+    // string literals, see `Lexer::lex_string_segment`
+    /// a string with no interpolation left to lex, terminated by the closing `"`
+    StringLit,
+    /// a chunk of literal text terminated by the start of an interpolation
+    StringFragment,
+    /// the `${` that opens an interpolation
+    InterpStart,
+    /// the `}` that closes an interpolation, handing control back to the string
+    InterpEnd,
 
     // Keywords
     Exit,
+    True,
+    False,
 
     // whitespace, comments and already handled tokens
     Ignore,
+    /// a `///` or `/** ... */` doc comment, see `Lexer::lex_div_or_comment`.
+    /// Unlike plain comments, kept by the `Iterator` impl instead of being
+    /// skipped, so a later pass can attach documentation to declarations.
+    DocComment,
 
     // unrecognized tokens
     Unknown,
@@ -60,7 +101,18 @@ type OnceMap<K, V> = OnceLock<RwLock<HashMap<K, V>>>;
 static KEYWORDS: OnceMap<&'static str, TokenType> = OnceLock::new();
 
 fn init_keywords() -> &'static RwLock<HashMap<&'static str, TokenType>> {
-    KEYWORDS.get_or_init(|| RwLock::new([("exit", TokenType::Exit)].iter().cloned().collect()))
+    KEYWORDS.get_or_init(|| {
+        RwLock::new(
+            [
+                ("exit", TokenType::Exit),
+                ("true", TokenType::True),
+                ("false", TokenType::False),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+        )
+    })
 }
 
 impl std::fmt::Display for TokenType {
@@ -90,23 +142,40 @@ impl Display for Token {
 // }
 
 /// lexer modes let us deal with things like nested string interpolations and
-/// unpaired delimiters
+/// unpaired delimiters. Modes are kept on a stack (see `Lexer::modes`), so a
+/// `${ ... }` inside a string can itself contain another string with its own
+/// `${ ... }`, each level resuming exactly the mode it interrupted.
+#[derive(Debug, Clone)]
 enum LexerMode {
     // starts in normal mode
     Normal,
+
+    /// inside a string literal, lexing fragment text. Entered on `"`, and
+    /// resumed whenever an `Interpolation` on top of it is popped by its
+    /// closing `}`.
+    String,
+
+    /// inside a `${ ... }`, lexing ordinary tokens until the `}` that
+    /// matches the `${` which pushed this mode. `brace_depth` counts any
+    /// unrelated `{`/`}` pairs nested inside, e.g. a future block or object
+    /// literal, so they don't get mistaken for the interpolation's own
+    /// closing brace.
+    Interpolation { brace_depth: usize },
 }
 
 #[derive(Clone)]
 pub struct Lexer {
-    reader: Reader,
+    cursor: SourceCursor,
     eh: Rc<ErrorHandler>,
+    modes: Vec<LexerMode>,
 }
 
 impl Lexer {
-    pub fn new(reader: Reader, eh: &Rc<ErrorHandler>) -> Self {
+    pub fn new(cursor: SourceCursor, eh: &Rc<ErrorHandler>) -> Self {
         Self {
-            reader,
+            cursor,
             eh: eh.clone(),
+            modes: vec![LexerMode::Normal],
         }
     }
 
@@ -123,10 +192,10 @@ impl Lexer {
         mappings.iter().find_map(|(cs, tt)| {
             cs.iter()
                 .enumerate()
-                .all(|(i, &c)| self.reader.peek_n(i) == c)
+                .all(|(i, &c)| self.cursor.peek_n(i) == c)
                 .then(|| {
                     (0..cs.len()).for_each(|_| {
-                        self.reader.next();
+                        self.cursor.advance();
                     });
                     *tt
                 })
@@ -136,40 +205,154 @@ impl Lexer {
     /// parses all tokens that start with a /
     fn lex_div_or_comment(&mut self) -> TokenType {
         use crate::lexer::TokenType as T;
-        match self.reader.peek() {
-            // comment
+        match self.cursor.front() {
+            // line comment, or a `///` doc comment
             Some('/') => {
-                while self.reader.peek() != Some('\n') && self.reader.peek().is_some() {
-                    self.reader.next();
+                let is_doc = self.cursor.peek_n(1) == Some('/');
+                while self.cursor.front() != Some('\n') && self.cursor.front().is_some() {
+                    self.cursor.advance();
+                }
+                if is_doc {
+                    T::DocComment
+                } else {
+                    T::Ignore
+                }
+            }
+            // block comment, or a `/** ... */` doc comment (but not the
+            // empty `/**/`). Nests, so `/* /* */ */` closes correctly.
+            Some('*') => {
+                let is_doc =
+                    self.cursor.peek_n(1) == Some('*') && self.cursor.peek_n(2) != Some('/');
+                self.cursor.advance(); // consume the '*' that opened the block
+                let mut depth = 1;
+                loop {
+                    match (self.cursor.front(), self.cursor.peek_n(1)) {
+                        (Some('/'), Some('*')) => {
+                            self.cursor.advance();
+                            self.cursor.advance();
+                            depth += 1;
+                        }
+                        (Some('*'), Some('/')) => {
+                            self.cursor.advance();
+                            self.cursor.advance();
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        (Some(_), _) => {
+                            self.cursor.advance();
+                        }
+                        (None, _) => {
+                            self.eh
+                                .clone()
+                                .unterminated_block_comment(self.cursor.pending());
+                            break;
+                        }
+                    }
+                }
+                if is_doc {
+                    T::DocComment
+                } else {
+                    T::Ignore
                 }
-                T::Ignore
             }
             _ => T::Slash,
         }
     }
 
+    /// Lex everything starting with the digit `normal_mode_next` already
+    /// consumed: a `0x`/`0o`/`0b`-prefixed integer, or a decimal integer or
+    /// float.
     fn lex_number_literal(&mut self) -> TokenType {
-        // helper for matching digit or digit separator, e.g. 123_456_789
+        if self.cursor.previous() == Some('0') {
+            let radix = match self.cursor.front() {
+                Some('x') => Some(('x', char::is_ascii_hexdigit as fn(&char) -> bool)),
+                Some('o') => Some(('o', (|c: &char| ('0'..='7').contains(c)) as fn(&char) -> bool)),
+                Some('b') => Some(('b', (|c: &char| matches!(c, '0' | '1')) as fn(&char) -> bool)),
+                _ => None,
+            };
+            if let Some((prefix, is_digit)) = radix {
+                self.cursor.advance(); // consume the radix marker
+                let mut digits = 0;
+                while self.cursor.front().is_some_and(|c| is_digit(&c) || c == '_') {
+                    self.cursor.advance();
+                    digits += 1;
+                }
+                if digits == 0 {
+                    self.eh
+                        .clone()
+                        .bad_number_literal(self.cursor.pending(), prefix);
+                }
+                return TokenType::IntLit;
+            }
+        }
+
+        self.lex_decimal_or_float()
+    }
+
+    /// Lex a plain decimal integer, or a float if a `.digit` fraction or an
+    /// `[eE][+-]?digits` exponent follows, or an imaginary literal if any of
+    /// those is followed directly by `i` (e.g. `3i`, `2.0i`). A `.` not
+    /// followed by a digit is left alone, so `1.method` or a future `1..2`
+    /// range still lex correctly: we have to peek two characters ahead
+    /// before committing.
+    fn lex_decimal_or_float(&mut self) -> TokenType {
         let is_digit_or_sep = |c: char| c.is_ascii_digit() || c == '_';
+        let mut is_float = false;
 
-        // match integer part
-        while self.reader.peek().is_some_and(is_digit_or_sep) {
-            self.reader.next();
+        // integer part
+        while self.cursor.front().is_some_and(is_digit_or_sep) {
+            self.cursor.advance();
         }
 
-        TokenType::IntLit
+        // fractional part
+        if self.cursor.front() == Some('.')
+            && self.cursor.peek_n(1).is_some_and(|c| c.is_ascii_digit())
+        {
+            is_float = true;
+            self.cursor.advance(); // consume '.'
+            while self.cursor.front().is_some_and(is_digit_or_sep) {
+                self.cursor.advance();
+            }
+        }
+
+        // exponent
+        if matches!(self.cursor.front(), Some('e' | 'E')) {
+            let sign_len = usize::from(matches!(self.cursor.peek_n(1), Some('+' | '-')));
+            if self.cursor.peek_n(1 + sign_len).is_some_and(|c| c.is_ascii_digit()) {
+                is_float = true;
+                for _ in 0..=sign_len {
+                    self.cursor.advance();
+                }
+                while self.cursor.front().is_some_and(is_digit_or_sep) {
+                    self.cursor.advance();
+                }
+            }
+        }
+
+        if self.cursor.front() == Some('i') {
+            self.cursor.advance();
+            return TokenType::ImaginaryLit;
+        }
+
+        if is_float {
+            TokenType::FloatLit
+        } else {
+            TokenType::IntLit
+        }
     }
 
     fn lex_identifier(&mut self) -> TokenType {
         while self
-            .reader
-            .peek()
+            .cursor
+            .front()
             .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
         {
-            self.reader.next();
+            self.cursor.advance();
         }
 
-        let text = self.reader.current.clone().into_string();
+        let text = self.cursor.pending().into_string();
         if let Some(type_) = init_keywords()
             .read()
             .unwrap_or_else(|_| internal_error!("poisoned lock"))
@@ -181,14 +364,106 @@ impl Lexer {
         }
     }
 
+    /// Lex the next run of literal text inside a string, starting right
+    /// after the opening `"` or after a `}` that just closed a nested
+    /// interpolation. Stops, without consuming it, the moment it sees `${`,
+    /// so the following call emits that as its own [`TokenType::InterpStart`].
+    fn lex_string_segment(&mut self) -> TokenType {
+        use TokenType as TT;
+        if self.cursor.front() == Some('$') && self.cursor.peek_n(1) == Some('{') {
+            self.cursor.advance();
+            self.cursor.advance();
+            self.modes.push(LexerMode::Interpolation { brace_depth: 0 });
+            return TT::InterpStart;
+        }
+
+        loop {
+            match self.cursor.front() {
+                Some('"') => {
+                    self.cursor.advance();
+                    self.modes.pop();
+                    return TT::StringLit;
+                }
+                Some('$') if self.cursor.peek_n(1) == Some('{') => return TT::StringFragment,
+                Some('\\') => {
+                    self.cursor.advance();
+                    if !self.lex_escape() {
+                        self.modes.pop();
+                        return TT::Unknown;
+                    }
+                }
+                Some(_) => {
+                    self.cursor.advance();
+                }
+                None => {
+                    self.eh.clone().unterminated_string(self.cursor.pending());
+                    self.modes.pop();
+                    return TT::Unknown;
+                }
+            }
+        }
+    }
+
+    /// Consume and validate whatever follows a `\` inside a string. Assumes
+    /// the `\` itself has already been consumed. Returns `false`, after
+    /// reporting the error, if the escape is malformed or input ends first.
+    fn lex_escape(&mut self) -> bool {
+        let Some(c) = self.cursor.advance() else {
+            self.eh.clone().unterminated_string(self.cursor.pending());
+            return false;
+        };
+        match c {
+            'n' | 't' | 'r' | '\\' | '"' | '$' => true,
+            'x' => self.lex_hex_escape(2),
+            'u' => self.lex_unicode_escape(),
+            _ => {
+                self.eh.clone().bad_escape(self.cursor.pending(), c);
+                false
+            }
+        }
+    }
+
+    /// `\xHH`: exactly `digits` hex digits.
+    fn lex_hex_escape(&mut self, digits: usize) -> bool {
+        for _ in 0..digits {
+            if self.cursor.front().is_some_and(|c| c.is_ascii_hexdigit()) {
+                self.cursor.advance();
+            } else {
+                self.eh.clone().bad_escape(self.cursor.pending(), 'x');
+                return false;
+            }
+        }
+        true
+    }
+
+    /// `\u{HEX}`: braces around one or more hex digits.
+    fn lex_unicode_escape(&mut self) -> bool {
+        if self.cursor.advance() != Some('{') {
+            self.eh.clone().bad_escape(self.cursor.pending(), 'u');
+            return false;
+        }
+        let mut digits = 0;
+        while self.cursor.front().is_some_and(|c| c.is_ascii_hexdigit()) {
+            self.cursor.advance();
+            digits += 1;
+        }
+        if digits == 0 || self.cursor.advance() != Some('}') {
+            self.eh.clone().bad_escape(self.cursor.pending(), 'u');
+            return false;
+        }
+        true
+    }
+
     fn normal_mode_next(&mut self) -> Option<Token> {
         use TokenType as TT;
-        let c = self.reader.next()?;
+        let c = self.cursor.advance()?;
         let token_type = match c {
             // unambiguously single-character tokens
             ';' => TT::Semicolon,
             ')' => TT::RightParen,
             '(' => TT::LeftParen,
+            ']' => TT::RightBracket,
+            '[' => TT::LeftBracket,
             ',' => TT::Comma,
             '+' => TT::Plus,
             '%' => TT::Percent,
@@ -200,16 +475,65 @@ impl Lexer {
                 .lex_postfixes(&[(&[Some('*')], TT::Pow), (&[], TT::Star)])
                 .unwrap_or_else(|| assert_unreachable!()),
 
+            '!' => self
+                .lex_postfixes(&[(&[Some('=')], TT::BangEquals), (&[], TT::Bang)])
+                .unwrap_or_else(|| assert_unreachable!()),
+            '<' => self
+                .lex_postfixes(&[(&[Some('=')], TT::LtEquals), (&[], TT::Lt)])
+                .unwrap_or_else(|| assert_unreachable!()),
+            '>' => self
+                .lex_postfixes(&[(&[Some('=')], TT::GtEquals), (&[], TT::Gt)])
+                .unwrap_or_else(|| assert_unreachable!()),
+
             // two character
             ':' => self
                 .lex_postfixes(&[(&[Some('=')], TT::ColonEquals)])
                 .unwrap_or_else(|| {
-                    self.eh
-                        .clone()
-                        .unexpected_char(self.reader.current.clone(), c);
+                    self.eh.clone().unexpected_char(self.cursor.pending(), c);
+                    TT::Unknown
+                }),
+            '=' => self
+                .lex_postfixes(&[(&[Some('=')], TT::EqualsEquals), (&[], TT::Equals)])
+                .unwrap_or_else(|| assert_unreachable!()),
+            '&' => self
+                .lex_postfixes(&[(&[Some('&')], TT::AmpAmp)])
+                .unwrap_or_else(|| {
+                    self.eh.clone().unexpected_char(self.cursor.pending(), c);
+                    TT::Unknown
+                }),
+            '|' => self
+                .lex_postfixes(&[(&[Some('|')], TT::PipePipe)])
+                .unwrap_or_else(|| {
+                    self.eh.clone().unexpected_char(self.cursor.pending(), c);
                     TT::Unknown
                 }),
 
+            // braces only matter for matching a `${ ... }`'s own closing
+            // brace against ones nested inside it, see `LexerMode::Interpolation`
+            '{' => {
+                if let Some(LexerMode::Interpolation { brace_depth }) = self.modes.last_mut() {
+                    *brace_depth += 1;
+                }
+                TT::LeftBrace
+            }
+            '}' => match self.modes.last_mut() {
+                Some(LexerMode::Interpolation { brace_depth }) if *brace_depth > 0 => {
+                    *brace_depth -= 1;
+                    TT::RightBrace
+                }
+                Some(LexerMode::Interpolation { .. }) => {
+                    self.modes.pop();
+                    TT::InterpEnd
+                }
+                _ => TT::RightBrace,
+            },
+
+            // strings
+            '"' => {
+                self.modes.push(LexerMode::String);
+                self.lex_string_segment()
+            }
+
             // ignore whitespace
             ' ' | '\n' | '\r' | '\t' => TT::Ignore,
 
@@ -221,17 +545,41 @@ impl Lexer {
 
             _ => {
                 log::trace!("unmatched char");
-                // self.eh
-                //     .clone()
-                //     .unexpected_char(self.reader.current.clone(), c);
+                // self.eh.clone().unexpected_char(self.cursor.pending(), c);
                 TT::Unknown
             }
         };
         Some(Token {
             token_type,
-            lexeme: self.reader.advance_tail(),
+            lexeme: self.cursor.consume().unwrap_or_else(|| self.cursor.pending()),
         })
     }
+
+    /// Resume lexing fragment text after a `}` closed a nested
+    /// interpolation, e.g. the `b"` tail in `"a${x}b"` once `${x}` is done.
+    fn string_mode_next(&mut self) -> Option<Token> {
+        let token_type = self.lex_string_segment();
+        Some(Token {
+            token_type,
+            lexeme: self.cursor.consume().unwrap_or_else(|| self.cursor.pending()),
+        })
+    }
+
+    /// Produce the next token without skipping `Ignore`. The plain
+    /// `Iterator` impl filters those out; [`Asi`] needs them, since whether
+    /// a newline occurred is exactly what it's deciding on.
+    fn next_raw(&mut self) -> Option<Token> {
+        match self.modes.last() {
+            Some(LexerMode::String) => self.string_mode_next(),
+            _ => self.normal_mode_next(),
+        }
+    }
+
+    /// Wrap this lexer in an automatic-semicolon-insertion pass, see [`Asi`].
+    /// Opt-in: the plain `Lexer` iterator keeps requiring explicit `;`.
+    pub fn with_asi(self) -> Asi {
+        Asi::new(self)
+    }
 }
 
 impl Iterator for Lexer {
@@ -239,7 +587,7 @@ impl Iterator for Lexer {
 
     fn next(&mut self) -> Option<Self::Item> {
         use TokenType as TT;
-        let ot = self.normal_mode_next();
+        let ot = self.next_raw();
         if ot.clone().is_some_and(|t| t.token_type == TT::Ignore) {
             return self.next();
         }
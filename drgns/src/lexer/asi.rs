@@ -0,0 +1,110 @@
+use super::{Lexer, Token, TokenType};
+use crate::source::SourceView;
+
+/// Tokens after which a newline can plausibly end a statement, e.g. the
+/// last token of `x := 1 + 2`. Anything else (operators, an open paren,
+/// keywords that expect more to follow) must not trigger insertion, so a
+/// multi-line expression keeps parsing as one.
+fn ends_statement(tt: TokenType) -> bool {
+    matches!(
+        tt,
+        TokenType::Identifier
+            | TokenType::IntLit
+            | TokenType::FloatLit
+            | TokenType::ImaginaryLit
+            | TokenType::StringLit
+            | TokenType::RightParen
+            | TokenType::RightBrace
+            | TokenType::Exit
+            | TokenType::True
+            | TokenType::False
+    )
+}
+
+/// Tokens that can plausibly open a new statement. Used to avoid inserting
+/// a semicolon before a newline that's actually just wrapping the *same*
+/// expression onto the next line, e.g.
+/// ```txt
+/// x
+/// + y
+/// ```
+fn begins_statement(tt: TokenType) -> bool {
+    matches!(
+        tt,
+        TokenType::Identifier
+            | TokenType::IntLit
+            | TokenType::FloatLit
+            | TokenType::ImaginaryLit
+            | TokenType::StringLit
+            | TokenType::LeftParen
+            | TokenType::Exit
+            | TokenType::True
+            | TokenType::False
+    )
+}
+
+/// Wraps a [`Lexer`] to synthesize a [`TokenType::Semicolon`] wherever a
+/// newline falls between a token that can end a statement and one that can
+/// start a new one, the same trick Go and JavaScript use so statements
+/// don't all need an explicit terminator. Opt-in via [`Lexer::with_asi`]:
+/// the raw, ASI-free token stream stays available through `Lexer` itself.
+pub struct Asi {
+    lexer: Lexer,
+    last: Option<TokenType>,
+    /// a real token pulled ahead to decide on insertion, returned on the
+    /// next call once the synthesized `Semicolon` (if any) has gone out
+    pending: Option<Token>,
+}
+
+impl Asi {
+    pub(super) fn new(lexer: Lexer) -> Self {
+        Self {
+            lexer,
+            last: None,
+            pending: None,
+        }
+    }
+}
+
+impl Iterator for Asi {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        if let Some(t) = self.pending.take() {
+            self.last = Some(t.token_type);
+            return Some(t);
+        }
+
+        let mut saw_newline = false;
+        loop {
+            let t = self.lexer.next_raw()?;
+            if t.token_type == TokenType::Ignore {
+                saw_newline |= t.lexeme.to_string().contains('\n');
+                continue;
+            }
+
+            if saw_newline
+                && self.last.is_some_and(ends_statement)
+                && begins_statement(t.token_type)
+            {
+                // zero-length span right where the real token starts, so
+                // diagnostics pointing at the synthesized `;` land right
+                // before it rather than quoting any source text
+                let marker = SourceView {
+                    arena: t.lexeme.arena.clone(),
+                    span: t.lexeme.span.start..t.lexeme.span.start,
+                    source_id: t.lexeme.source_id,
+                };
+                self.last = Some(TokenType::Semicolon);
+                self.pending = Some(t);
+                return Some(Token {
+                    token_type: TokenType::Semicolon,
+                    lexeme: marker,
+                });
+            }
+
+            self.last = Some(t.token_type);
+            return Some(t);
+        }
+    }
+}
@@ -4,7 +4,7 @@ use std::{
     sync::{OnceLock, RwLock},
 };
 
-use crate::source::{Reader, SourceArena};
+use crate::source::SourceArena;
 
 use super::{Lexer, OnceMap, TokenType};
 
@@ -19,6 +19,8 @@ fn init_str_2_tokens() -> &'static RwLock<HashMap<&'static str, TokenType>> {
             [
                 ("(", TT::LeftParen),
                 (")", TT::RightParen),
+                ("[", TT::LeftBracket),
+                ("]", TT::RightBracket),
                 (",", TT::Comma),
                 ("+", TT::Plus),
                 ("*", TT::Star),
@@ -27,9 +29,26 @@ fn init_str_2_tokens() -> &'static RwLock<HashMap<&'static str, TokenType>> {
                 ("/", TT::Slash),
                 ("-", TT::Minus),
                 (":=", TT::ColonEquals),
+                ("=", TT::Equals),
+                ("!", TT::Bang),
+                ("!=", TT::BangEquals),
+                ("<", TT::Lt),
+                ("<=", TT::LtEquals),
+                (">", TT::Gt),
+                (">=", TT::GtEquals),
+                ("==", TT::EqualsEquals),
+                ("&&", TT::AmpAmp),
+                ("||", TT::PipePipe),
                 //("", TT::Identifier),
                 //("", TT::IntLit),
+                //("", TT::FloatLit),
+                //("", TT::ImaginaryLit),
                 ("exit", TT::Exit),
+                ("true", TT::True),
+                ("false", TT::False),
+                ("{", TT::LeftBrace),
+                ("}", TT::RightBrace),
+                ("\"hi\"", TT::StringLit),
                 (" ", TT::Ignore),
                 ("\t", TT::Ignore),
                 ("\r", TT::Ignore),
@@ -56,6 +75,8 @@ pub fn tokens_2_str(tt: TokenType) -> &'static str {
         TT::Semicolon => ";",
         TT::LeftParen => "(",
         TT::RightParen => ")",
+        TT::LeftBracket => "[",
+        TT::RightBracket => "]",
         TT::Comma => ",",
         TT::Plus => "+",
         TT::Star => "*",
@@ -63,15 +84,57 @@ pub fn tokens_2_str(tt: TokenType) -> &'static str {
         TT::Slash => "/",
         TT::Minus => "-",
         TT::ColonEquals => ":=",
+        TT::Equals => "=",
+        TT::LeftBrace => "{",
+        TT::RightBrace => "}",
         TT::Identifier => "andy",
         TT::IntLit => "42",
+        TT::FloatLit => "4.2",
+        TT::ImaginaryLit => "3i",
+        TT::StringLit => "\"hi\"",
+        // these only ever appear alongside other tokens as part of a larger
+        // string/interpolation construct, see `is_context_sensitive`
+        TT::StringFragment => "\"frag",
+        TT::InterpStart => "${",
+        TT::InterpEnd => "}",
         TT::Ignore => " ",
         TT::Unknown => "?",
         TT::Pow => "**",
+        TT::Bang => "!",
+        TT::BangEquals => "!=",
+        TT::Lt => "<",
+        TT::LtEquals => "<=",
+        TT::Gt => ">",
+        TT::GtEquals => ">=",
+        TT::EqualsEquals => "==",
+        TT::AmpAmp => "&&",
+        TT::PipePipe => "||",
         TT::Exit => "exit",
+        TT::True => "true",
+        TT::False => "false",
+        // has no trailing whitespace of its own -- it swallows through the
+        // end of the line -- so it doesn't fit the single-token-plus-space
+        // shape `lex_single_tokens`/`lex_token_pairs` assume either, see
+        // `is_context_sensitive`
+        TT::DocComment => "///doc",
     }
 }
 
+/// `lex_single_tokens`/`lex_token_pairs` assume a token type's
+/// `tokens_2_str` text lexes, on its own, to exactly one token of that type
+/// followed by whitespace. `StringFragment`, `InterpStart` and `InterpEnd`
+/// never appear in isolation like that -- they're always followed by more
+/// of the string or interpolation they're part of. `DocComment` swallows
+/// any trailing whitespace as part of the comment itself. All are exercised
+/// by dedicated tests instead.
+pub fn is_context_sensitive(tt: TokenType) -> bool {
+    use TokenType as TT;
+    matches!(
+        tt,
+        TT::StringFragment | TT::InterpStart | TT::InterpEnd | TT::DocComment
+    )
+}
+
 pub fn lel(s: &str) -> u32 {
     match s {
         "lel" => 1,
@@ -1,58 +1,88 @@
 //! An iterator that keeps a lookahead until it is committed, similar to Multipeek
 
-#[deprecated]
-pub struct Lookahead<I: Iterator + Clone>
+use std::collections::VecDeque;
+
+/// Buffers items from an underlying iterator so arbitrary lookahead is
+/// available through the [`Cursor`] interface, replacing the old
+/// `Lookahead`/`Multipeek`-style wrapper. This is the same abstraction the
+/// lexer uses over source characters (see `source::SourceCursor`), applied
+/// here one level up, over a stream of tokens.
+pub struct IterCursor<I: Iterator>
 where
     I::Item: Clone,
 {
-    main: I,
-    branch: I,
-    pub current: Option<I::Item>,
+    inner: I,
+    buf: VecDeque<I::Item>,
+    front: usize,
+    previous: Option<I::Item>,
 }
 
-impl<I: Iterator + Clone> Lookahead<I>
+impl<I: Iterator> IterCursor<I>
 where
-    <I as Iterator>::Item: Clone,
+    I::Item: Clone,
 {
-    pub fn peek(&mut self) -> Option<I::Item> {
-        self.current = self.branch.next();
-        self.current.clone()
-    }
-
-    pub fn reset(&mut self) {
-        self.branch = self.main.clone()
+    pub fn new(inner: I) -> Self {
+        Self {
+            inner,
+            buf: VecDeque::new(),
+            front: 0,
+            previous: None,
+        }
     }
 
-    pub fn commit(&mut self) {
-        self.main = self.branch.clone()
+    fn fill_to(&mut self, i: usize) {
+        while self.buf.len() <= i {
+            match self.inner.next() {
+                Some(item) => self.buf.push_back(item),
+                None => break,
+            }
+        }
     }
 }
 
-impl<I: Iterator + Clone> Iterator for Lookahead<I>
+impl<I: Iterator> Cursor<I::Item, Vec<I::Item>> for IterCursor<I>
 where
-    <I as Iterator>::Item: Clone,
+    I::Item: Clone,
 {
-    type Item = I::Item;
+    fn peek_n(&mut self, i: usize) -> Option<I::Item> {
+        self.fill_to(self.front + i);
+        self.buf.get(self.front + i).cloned()
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.current = self.main.next();
-        self.branch = self.main.clone();
-        self.current.clone()
+    fn previous(&self) -> Option<I::Item> {
+        self.previous.clone()
     }
-}
 
-pub fn lookahead<I: IntoIterator>(iterable: I) -> Lookahead<I::IntoIter>
-where
-    <I as IntoIterator>::IntoIter: Clone,
-    <I as IntoIterator>::Item: Clone,
-{
-    let main = iterable.into_iter();
-    let branch = main.clone();
-    let current = None;
-    Lookahead {
-        main,
-        branch,
-        current,
+    fn peek_back_n(&self, i: usize) -> Option<I::Item> {
+        self.buf.get(i).cloned()
+    }
+
+    fn window_len(&self) -> usize {
+        self.front
+    }
+
+    fn window_is_empty(&self) -> bool {
+        self.front == 0
+    }
+
+    fn advance(&mut self) -> Option<I::Item> {
+        self.fill_to(self.front);
+        let ret = self.buf.get(self.front).cloned();
+        if ret.is_some() {
+            self.front += 1;
+            self.previous = ret.clone();
+        }
+        ret
+    }
+
+    fn consume(&mut self) -> Option<Vec<I::Item>> {
+        let ret = self.buf.drain(..self.front).collect();
+        self.front = 0;
+        Some(ret)
+    }
+
+    fn reset(&mut self) {
+        self.front = 0;
     }
 }
 
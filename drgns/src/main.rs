@@ -66,15 +66,113 @@ enum Commands {
 fn main() {
     let cli = <Cli as clap::Parser>::parse();
     match &cli.command {
-        Some(Commands::Run{input: _}) => todo!(),
+        Some(Commands::Run { input: Some(path) }) => run_file(path),
+        Some(Commands::Run { input: None }) => repl(),
         Some(Commands::Build{input: _}) => todo!(),
         Some(Commands::Check{input: _}) => todo!(),
         None => repl(),
     }
 }
 
+/// Build and run a single file, end to end: read it from disk, intern it
+/// under its own filename (rather than the REPL's shared `source_id` 0, see
+/// [`SourceArena::intern_file`]), and evaluate it as a single expression.
+fn run_file(path: &str) {
+    let source = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        fatal!(format!("reading '{}': {}", path, e));
+    });
+
+    let src = Rc::new(SourceArena::new());
+    let eh = Rc::new(ErrorHandler::new(&src));
+    let view = src.intern_file(path.to_string(), source);
+    let mut pr = Parser::new(Lexer::new(source::SourceCursor::from_view(&view), &eh), &eh);
+    if let Some(expr) = pr.parse_expression() {
+        if !eh.had_error() {
+            let mut eval = eval::ExpressionEval::new(&eh, values::OverflowPolicy::default());
+            expr.walk(&mut eval);
+            println!("{:?}", eval.result());
+        }
+    }
+    eh.report_all();
+}
+
 fn repl() {
-    let eh = Arc::new(ErrorHandler::new())
+    let src = Rc::new(SourceArena::new());
+    let eh = Rc::new(ErrorHandler::new(&src));
+    let mut buffer = String::new();
+    let mut depth: i32 = 0;
+    let mut in_string = false;
+    loop {
+        if depth > 0 {
+            print!("..{}> ", depth);
+        } else {
+            print!("> ");
+        }
+        std::io::stdout().flush().unwrap_or_else(|_| {
+            fatal!("stdout cannot be written to");
+        });
+
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line).unwrap_or_else(|_| {
+            fatal!("stdin cannot be read");
+        });
+        let (delta, still_in_string) = delim_depth(&line, in_string);
+        depth = (depth + delta).max(0);
+        in_string = still_in_string;
+        buffer.push_str(&line);
+
+        // keep reading lines until every opened delimiter is closed and no
+        // string literal is left dangling open
+        if depth > 0 || in_string {
+            continue;
+        }
+
+        src.intern(std::mem::take(&mut buffer));
+        let mut pr = Parser::new(Lexer::new(source::SourceCursor::from_arena(&src), &eh), &eh);
+        if let Some(expr) = pr.parse_expression() {
+            if !eh.had_error() {
+                let mut eval = eval::ExpressionEval::new(&eh, values::OverflowPolicy::default());
+                expr.walk(&mut eval);
+                println!("{:?}", eval.result());
+            }
+        }
+        eh.report_all();
+    }
+}
+
+/// count the net number of open `(`/`)`, `[`/`]` and `{`/`}` in `line`,
+/// given whether `line` starts out inside a string literal left open by a
+/// previous line, so the REPL can tell whether the accumulated input still
+/// needs another line before it forms a complete expression.
+///
+/// Returns the depth delta and whether `line` ends inside a (possibly
+/// escaped) string literal -- the caller threads that back in as
+/// `in_string` on the next line, the same way it threads `depth`, so a
+/// string literal left open across a line break is still recognized as
+/// unterminated instead of being re-derived (and lost) from scratch.
+/// Delimiters inside a string literal don't count -- `"("` doesn't open
+/// anything.
+fn delim_depth(line: &str, mut in_string: bool) -> (i32, bool) {
+    let mut depth = 0;
+    let mut escaped = false;
+    for c in line.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    (depth, in_string)
 }
 
 #[deprecated]
@@ -134,9 +232,9 @@ impl Interpreter {
 
     fn run(&mut self, input: String) {
         self.src.intern(input);
-        let mut eval = eval::ExpressionEval::new();
+        let mut eval = eval::ExpressionEval::new(&self.eh, values::OverflowPolicy::default());
         self.pr.parse_expression().unwrap().walk(&mut eval);
-        println!("{:?}", eval);
+        println!("{:?}", eval.result());
     }
 }
 
@@ -149,7 +247,7 @@ fn old_main() -> ! {
     let mut i = Interpreter {
         args: <Args as clap::Parser>::parse(),
         src: src.clone(),
-        pr: Parser::new(Lexer::new(source::Reader::from_arena(&src), &eh), &eh),
+        pr: Parser::new(Lexer::new(source::SourceCursor::from_arena(&src), &eh), &eh),
         eh: eh.clone(),
     };
 
@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use crate::source::Located;
 use crate::values::Value;
 
 #[derive(Debug, Clone, derive_more::Display)]
@@ -7,6 +8,14 @@ pub enum Expression {
     BE(BinExpression),
     UE(UnExpression),
     LE(LitExpression),
+    LOE(LogicExpression),
+    LSE(ListExpression),
+    IAE(IndexAssignExpression),
+    /// placeholder produced by panic-mode recovery in `parse_primary`/
+    /// `parse_grouping` in place of a subtree that failed to parse, so the
+    /// rest of a malformed file can still be parsed and evaluated
+    #[display(fmt = "<error>")]
+    Error,
 }
 
 impl Expression {
@@ -18,6 +27,15 @@ impl Expression {
             Self::BE(be) => be.walk(v),
             Self::UE(ue) => ue.walk(v),
             Self::LE(le) => le.walk(v),
+            // unlike the other variants, `LogicExpression` doesn't walk its
+            // own children here -- `&&`/`||` only evaluate their rhs
+            // conditionally, so the visitor has to be in control of that
+            // decision instead of both sides being walked unconditionally
+            // up front
+            Self::LOE(loe) => loe.walk(v),
+            Self::LSE(lse) => lse.walk(v),
+            Self::IAE(iae) => iae.walk(v),
+            Self::Error => v.visit_error_expression(),
         }
         v.visit_expression(self);
     }
@@ -53,6 +71,16 @@ pub enum BinOperator {
     Mod,
     Add,
     Sub,
+
+    /// `lhs[rhs]`, indexing into a list
+    Index,
+
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
 }
 
 impl BinOperator {
@@ -70,6 +98,13 @@ impl Display for BinOperator {
             Self::Mod => write!(f, "%"),
             Self::Add => write!(f, "+"),
             Self::Sub => write!(f, "-"),
+            Self::Index => write!(f, "[]"),
+            Self::Lt => write!(f, "<"),
+            Self::Le => write!(f, "<="),
+            Self::Gt => write!(f, ">"),
+            Self::Ge => write!(f, ">="),
+            Self::Eq => write!(f, "=="),
+            Self::Ne => write!(f, "!="),
         }
     }
 }
@@ -97,6 +132,7 @@ impl Display for UnExpression {
 #[derive(Debug, Clone)]
 pub enum UnOperator {
     Neg,
+    Not,
 }
 
 impl UnOperator {
@@ -109,12 +145,56 @@ impl Display for UnOperator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Neg => write!(f, "-"),
+            Self::Not => write!(f, "!"),
+        }
+    }
+}
+
+/// `&&`/`||`: unlike every other [`BinOperator`], these short-circuit --
+/// `rhs` isn't evaluated at all if `lhs` already determines the result.
+/// Kept as its own AST node instead of a `BinExpression` variant so
+/// [`Expression::walk`] can leave walking `rhs` up to the visitor rather
+/// than doing it unconditionally before the visitor ever sees the node.
+#[derive(Debug, Clone)]
+pub struct LogicExpression {
+    pub lhs: Box<Expression>,
+    pub op: LogicOperator,
+    pub rhs: Box<Expression>,
+}
+
+impl LogicExpression {
+    fn walk(&self, v: &mut impl Visitor<()>) {
+        v.visit_logic_expression(self);
+    }
+}
+
+impl Display for LogicExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({} {} {})", self.op, self.lhs, self.rhs)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum LogicOperator {
+    And,
+    Or,
+}
+
+impl Display for LogicOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::And => write!(f, "&&"),
+            Self::Or => write!(f, "||"),
         }
     }
 }
 
+/// A literal value together with the span it was parsed from. The span
+/// isn't needed by evaluation today, but it's what a future type checker
+/// or runtime-error report would point at instead of the stack machine's
+/// "no span here" fallback -- see [`Located`].
 #[derive(Debug, Clone, derive_more::Display)]
-pub struct LitExpression(pub Value);
+pub struct LitExpression(pub Located<Value>);
 
 impl LitExpression {
     fn walk(&self, v: &mut impl Visitor<()>) {
@@ -122,6 +202,63 @@ impl LitExpression {
     }
 }
 
+/// `[e1, e2, ...]`: a list literal. Unlike `LitExpression`, its elements
+/// aren't known until each sub-expression is evaluated, so it walks `items`
+/// like any other composite node instead of carrying a `Value` up front.
+#[derive(Debug, Clone)]
+pub struct ListExpression {
+    pub items: Vec<Expression>,
+}
+
+impl ListExpression {
+    /// walked in reverse so the last-pushed (and thus first-popped) stack
+    /// slot is `items[0]`, letting the visitor collect them back into their
+    /// original order with a single pass of pops
+    fn walk(&self, v: &mut impl Visitor<()>) {
+        for item in self.items.iter().rev() {
+            item.walk(v);
+        }
+        v.visit_list_expression(self);
+    }
+}
+
+impl Display for ListExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(list")?;
+        for item in &self.items {
+            write!(f, " {}", item)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// `target[index] = value`: a functional list update. There are no
+/// variable bindings yet (see the parser's `parse_assignment`), so
+/// `target` doesn't name a place to mutate -- this evaluates `target`,
+/// replaces the element at `index` the same way [`crate::values::Value::index_assign`]
+/// does, and produces the updated list as the expression's own value.
+#[derive(Debug, Clone)]
+pub struct IndexAssignExpression {
+    pub target: Box<Expression>,
+    pub index: Box<Expression>,
+    pub value: Box<Expression>,
+}
+
+impl IndexAssignExpression {
+    fn walk(&self, v: &mut impl Visitor<()>) {
+        self.value.walk(v);
+        self.index.walk(v);
+        self.target.walk(v);
+        v.visit_index_assign_expression(self);
+    }
+}
+
+impl Display for IndexAssignExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "([]= {} {} {})", self.target, self.index, self.value)
+    }
+}
+
 pub trait Visitor<T> {
     fn visit_expression(&mut self, e: &Expression) -> T;
     fn visit_bin_expression(&mut self, be: &BinExpression) -> T;
@@ -129,4 +266,15 @@ pub trait Visitor<T> {
     fn visit_un_expression(&mut self, ue: &UnExpression) -> T;
     fn visit_un_operator(&mut self, uo: &UnOperator) -> T;
     fn visit_lit_expression(&mut self, le: &LitExpression) -> T;
+    /// Unlike the other `visit_*` methods, the implementor is responsible
+    /// for walking `le.lhs`/`le.rhs` itself (see [`LogicExpression`]),
+    /// since whether `rhs` gets walked at all depends on `lhs`'s value.
+    fn visit_logic_expression(&mut self, le: &LogicExpression) -> T;
+    /// visits a [`ListExpression`] once every item has already been walked
+    fn visit_list_expression(&mut self, lse: &ListExpression) -> T;
+    /// visits an [`IndexAssignExpression`] once `target`, `index` and
+    /// `value` have already been walked
+    fn visit_index_assign_expression(&mut self, iae: &IndexAssignExpression) -> T;
+    /// visits an [`Expression::Error`] placeholder; has no children to walk
+    fn visit_error_expression(&mut self) -> T;
 }
@@ -0,0 +1,93 @@
+use std::{env, fs, path::PathBuf, rc::Rc};
+
+use crate::{
+    eh::ErrorHandler,
+    lexer::Lexer,
+    source::{SourceArena, SourceCursor},
+};
+
+use super::Parser;
+
+/// Parses `source` and renders whichever side of it is interesting: the
+/// fully parenthesized S-expression for a clean parse, or every collected
+/// diagnostic's message (one per line, in report order) if parsing raised
+/// any -- the same branch a human squinting at `dump_ast` output vs. a
+/// REPL error would make by hand.
+fn render(source: &str) -> String {
+    let arena = Rc::new(SourceArena::new());
+    let eh = Rc::new(ErrorHandler::new(&arena));
+    arena.intern(source.to_string());
+    let lexer = Lexer::new(SourceCursor::from_arena(&arena), &eh);
+    let expr = Parser::new(lexer, &eh).parse_expression();
+
+    let diagnostics = eh.diagnostics();
+    if !diagnostics.is_empty() {
+        return diagnostics
+            .iter()
+            .map(|d| d.message())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    match expr {
+        Some(e) => e.to_string(),
+        None => "<no expression>".to_string(),
+    }
+}
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/parser/test/fixtures")
+}
+
+/// Snapshot corpus for the parser: every `src/parser/test/fixtures/*.ds` file
+/// is parsed and compared against its `.expected` sibling. Run with
+/// `BLESS=1` to regenerate the `.expected` files from the current output
+/// instead of asserting against them, e.g. after a deliberate precedence
+/// or error-message change.
+#[test]
+fn golden_fixtures() {
+    let bless = env::var_os("BLESS").is_some();
+    let dir = fixtures_dir();
+
+    let mut fixtures: Vec<_> = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("reading fixtures dir {}: {}", dir.display(), e))
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ds"))
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no .ds fixtures found in {}", dir.display());
+
+    let mut mismatches = vec![];
+    for ds in fixtures {
+        let expected_path = ds.with_extension("expected");
+        let source = fs::read_to_string(&ds)
+            .unwrap_or_else(|e| panic!("reading fixture {}: {}", ds.display(), e));
+        let actual = render(&source);
+
+        if bless {
+            fs::write(&expected_path, &actual)
+                .unwrap_or_else(|e| panic!("writing {}: {}", expected_path.display(), e));
+            continue;
+        }
+
+        let expected = fs::read_to_string(&expected_path).unwrap_or_else(|e| {
+            panic!("reading expected file {}: {}", expected_path.display(), e)
+        });
+        if actual != expected {
+            mismatches.push(format!(
+                "{}:\n  expected: {:?}\n  actual:   {:?}",
+                ds.display(),
+                expected,
+                actual
+            ));
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "{} golden fixture(s) mismatched:\n{}",
+        mismatches.len(),
+        mismatches.join("\n")
+    );
+}
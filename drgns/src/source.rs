@@ -6,8 +6,8 @@ mod view;
 pub use view::*;
 mod string;
 pub use string::*;
-mod reader;
-pub use reader::*;
+mod located;
+pub use located::*;
 
 /// A piece of source code, either a file or a REPL logical line
 pub struct Source {
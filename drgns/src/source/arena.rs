@@ -2,19 +2,128 @@ use std::{
     cell::{Cell, RefCell},
     collections::HashMap,
     fmt::Display,
-    rc::Rc,
+    ops::Range,
+    rc::{Rc, Weak},
     sync::{RwLock, RwLockReadGuard},
 };
 
 use bimap::BiMap;
 
+use crate::lookahead::Cursor;
+
 use super::view::SourceView;
 
+/// A resolved human-readable location within a source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineColumn {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// Interned reference to one of the files (or REPL buffers) held by a
+/// [`SourceArena`]. This is the same id stored in [`SourceView::source_id`];
+/// the newtype exists so code that only needs to name a file -- not carry a
+/// span into it -- doesn't have to pass a bare `u16` around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileRef(pub(super) u16);
+
+impl FileRef {
+    /// The raw `source_id` this ref wraps, for callers outside
+    /// `crate::source` that need it as a plain `u16` (e.g. as a sort key).
+    pub fn id(&self) -> u16 {
+        self.0
+    }
+}
+
+impl Display for FileRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Maps char offsets to line/column pairs for every interned source file.
+///
+/// At intern time, the offset of each line start is recorded into a sorted
+/// `Vec<usize>` keyed by `source_id`. Resolving an offset is then a binary
+/// search over that vector rather than a linear scan of the source text.
+#[derive(Debug, Default)]
+struct SourceMap {
+    line_starts: HashMap<u16, Vec<usize>>,
+}
+
+impl SourceMap {
+    /// Record the offsets of every line start found while interning `src`,
+    /// which begins at arena offset `start`. The first chunk recorded for a
+    /// `source_id` also records `start` itself as that file's first line
+    /// start.
+    fn record(&mut self, source_id: u16, start: usize, src: &str) {
+        let starts = self.line_starts.entry(source_id).or_insert_with(|| vec![start]);
+        let mut offset = start;
+        for c in src.chars() {
+            offset += 1;
+            if c == '\n' {
+                starts.push(offset);
+            }
+        }
+    }
+
+    fn resolve(&self, source_id: u16, offset: usize) -> LineColumn {
+        let starts = self
+            .line_starts
+            .get(&source_id)
+            .map(Vec::as_slice)
+            .unwrap_or(&[0]);
+        let line = starts.partition_point(|&s| s <= offset).saturating_sub(1);
+        let col = offset - starts[line];
+        LineColumn { line, col }
+    }
+}
+
+/// Records, for every `source_id`, the (possibly non-contiguous) ranges of
+/// the shared buffer that belong to it. Files are normally interned in one
+/// contiguous chunk, but the REPL's id 0 is extended one line at a time and
+/// may have other files interned in between, so each id keeps a list of
+/// ranges rather than a single one.
+#[derive(Debug, Default)]
+struct FileRanges {
+    ranges: HashMap<u16, Vec<Range<usize>>>,
+}
+
+impl FileRanges {
+    fn record(&mut self, source_id: u16, range: Range<usize>) {
+        self.ranges.entry(source_id).or_default().push(range);
+    }
+
+    /// The full text belonging to `source_id`, in recording order.
+    fn text(&self, source_id: u16, data: &[char]) -> String {
+        self.ranges
+            .get(&source_id)
+            .into_iter()
+            .flatten()
+            .flat_map(|r| data[r.clone()].iter())
+            .collect()
+    }
+
+    /// Translate a global buffer offset into an offset relative to the
+    /// start of `source_id`'s own text, for handing to `ariadne`.
+    fn to_local(&self, source_id: u16, global: usize) -> usize {
+        let mut acc = 0;
+        for r in self.ranges.get(&source_id).into_iter().flatten() {
+            if r.contains(&global) || r.end == global {
+                return acc + (global - r.start);
+            }
+            acc += r.len();
+        }
+        acc
+    }
+}
+
 #[derive(Debug)]
-#[deprecated]
 pub struct SourceArena {
     data: RwLock<Vec<char>>,
     ids: RefCell<BiMap<String, u16>>,
+    map: RefCell<SourceMap>,
+    files: RefCell<FileRanges>,
 }
 
 impl Default for SourceArena {
@@ -28,6 +137,8 @@ impl SourceArena {
         let mut ret = Self {
             data: RwLock::new(vec![]),
             ids: RefCell::new(BiMap::new()),
+            map: RefCell::new(SourceMap::default()),
+            files: RefCell::new(FileRanges::default()),
         };
         ret.ids.borrow_mut().insert("REPL".to_string(), 0);
         ret
@@ -36,17 +147,79 @@ impl SourceArena {
     /// Intern a single string of raw source code, including newlines.
     ///
     /// You may intern parts of a single line, or multiple lines as well.
+    /// All REPL input shares the stable `source_id` 0, registered in `new`.
     pub fn intern(self: &Rc<Self>, src: String) -> SourceView {
         log::trace!("interning string: '{:?}'", src);
         let start = self.len();
+        let source_id = *self
+            .ids
+            .borrow()
+            .get_by_left("REPL")
+            .expect("REPL source_id is always registered");
+        self.map.borrow_mut().record(source_id, start, &src);
+        let end = start + src.chars().count();
+        self.files.borrow_mut().record(source_id, start..end);
         self.data.write().unwrap().extend(src.chars());
         SourceView {
             arena: Rc::<SourceArena>::downgrade(self),
-            span: start..(start + src.len()),
-            source_id: todo!(),
+            span: start..end,
+            source_id,
         }
     }
 
+    /// Intern `src` as the contents of `filename`, registering a fresh
+    /// [`FileRef`] for it the first time it's seen. Interning the same
+    /// filename again (e.g. appending to a file already open in an
+    /// LSP-style buffer) reuses its existing id.
+    ///
+    /// This is the multi-file counterpart to [`Self::intern`], which always
+    /// writes into the single, pre-registered `REPL` id.
+    pub fn intern_file(self: &Rc<Self>, filename: String, src: String) -> SourceView {
+        log::trace!("interning file '{}': '{:?}'", filename, src);
+        let start = self.len();
+        let source_id = {
+            let mut ids = self.ids.borrow_mut();
+            if let Some(&id) = ids.get_by_left(&filename) {
+                id
+            } else {
+                let id = ids.len() as u16;
+                ids.insert(filename, id);
+                id
+            }
+        };
+        self.map.borrow_mut().record(source_id, start, &src);
+        let end = start + src.chars().count();
+        self.files.borrow_mut().record(source_id, start..end);
+        self.data.write().unwrap().extend(src.chars());
+        SourceView {
+            arena: Rc::<SourceArena>::downgrade(self),
+            span: start..end,
+            source_id,
+        }
+    }
+
+    /// Resolve a [`FileRef`] (or raw `source_id`) back to the filename it
+    /// was registered under, for diagnostics that need to name the file an
+    /// error came from.
+    pub fn filename(&self, file: FileRef) -> Option<String> {
+        self.ids.borrow().get_by_right(&file.0).cloned()
+    }
+
+    /// The full text interned under `file`, standalone -- not the whole
+    /// arena. Used by diagnostic emitters, which render one file's snippet
+    /// at a time rather than the concatenation of every interned source.
+    pub fn file_text(&self, file: FileRef) -> String {
+        self.files.borrow().text(file.0, &self.data.read().unwrap())
+    }
+
+    /// Translate a `[lo, hi)` span given in arena-global offsets into a span
+    /// relative to the start of `file`'s own text, as returned by
+    /// [`Self::file_text`].
+    pub fn local_span(&self, file: FileRef, span: Range<usize>) -> Range<usize> {
+        let files = self.files.borrow();
+        files.to_local(file.0, span.start)..files.to_local(file.0, span.end)
+    }
+
     pub fn len(&self) -> usize {
         self.data.read().unwrap().len()
     }
@@ -58,6 +231,17 @@ impl SourceArena {
     pub fn inner(&self) -> RwLockReadGuard<'_, Vec<char>> {
         self.data.read().unwrap()
     }
+
+    /// Resolve a char offset into a `line:col` pair for diagnostics.
+    pub fn resolve(&self, source_id: u16, offset: usize) -> LineColumn {
+        self.map.borrow().resolve(source_id, offset)
+    }
+
+    /// Resolve a `[lo, hi)` span into its start/end `LineColumn`s, so
+    /// multi-line spans can be underlined correctly.
+    pub fn span_to_range(&self, source_id: u16, lo: usize, hi: usize) -> (LineColumn, LineColumn) {
+        (self.resolve(source_id, lo), self.resolve(source_id, hi))
+    }
 }
 
 impl Display for SourceArena {
@@ -68,116 +252,98 @@ impl Display for SourceArena {
 }
 
 
+/// Production replacement for the deprecated `Reader`, implementing
+/// [`Cursor`] directly over the source arena. This gives the lexer
+/// arbitrary lookahead, one-token lookbehind, and span-building through a
+/// single interface instead of the bespoke `Reader`/`Lookahead` pair.
 #[derive(Clone)]
-#[deprecated]
-pub struct Reader {
-    pub current: SourceView,
-    boundary: ReaderBounds,
+pub struct SourceCursor {
+    arena: Weak<SourceArena>,
+    source_id: u16,
+    back: usize,
+    front: usize,
 }
 
-#[deprecated]
-#[derive(Clone)]
-enum ReaderBounds {
-    Absolute,
-    Relative(SourceView),
-}
-
-/// fast read-only iterator over arena
-///
-/// reader can be advanced one character at a time simply by using it as
-/// an iterator, or the start and end can be advanced separately for
-/// lexing.
-impl Reader {
-    /// crate a new reader that traverses the entire arena from the start
+impl SourceCursor {
+    /// create a cursor that traverses the entire arena from the start,
+    /// tagging every span it builds with the `REPL` source id
     pub fn from_arena(s: &Rc<SourceArena>) -> Self {
         Self {
-            current: SourceView {
-                arena: Rc::downgrade(s),
-                span: 0..0,
-                source_id: todo!(),
-            },
-            boundary: ReaderBounds::Absolute,
+            arena: Rc::downgrade(s),
+            source_id: 0,
+            back: 0,
+            front: 0,
         }
     }
 
-    pub fn from_span(s: SourceView) -> Self {
+    /// create a cursor starting at `view`'s own offset into the arena and
+    /// tagging every span it builds with `view`'s `source_id`, instead of
+    /// always starting at 0 and tagging `REPL` like [`Self::from_arena`].
+    /// This is what [`SourceArena::intern_file`] callers need: a file's text
+    /// generally doesn't start at the beginning of the arena's shared
+    /// backing buffer once anything else has been interned before it.
+    pub fn from_view(view: &SourceView) -> Self {
         Self {
-            current: SourceView {
-                arena: s.arena.clone(),
-                span: 0..0,
-                source_id: todo!(),
-            },
-            boundary: ReaderBounds::Relative(s),
+            arena: view.arena.clone(),
+            source_id: view.source_id,
+            back: view.span.start,
+            front: view.span.start,
         }
     }
 
-    pub fn abs_bounds(&self) -> (usize, usize) {
-        match &self.boundary {
-            ReaderBounds::Absolute => (0, self.current.arena.upgrade().unwrap().len()),
-            ReaderBounds::Relative(s) => (s.span.start, s.span.end),
-        }
+    fn get(&self, i: usize) -> Option<char> {
+        self.arena.upgrade()?.get(i)
     }
 
-    pub fn rel_bounds(&self) -> usize {
-        match &self.boundary {
-            ReaderBounds::Absolute => self.current.arena.upgrade().unwrap().len(),
-            ReaderBounds::Relative(s) => s.span.len(),
+    /// peek at the characters currently pending between the back and front
+    /// cursors, without committing them via [`Cursor::consume`]
+    pub fn pending(&self) -> SourceView {
+        SourceView {
+            arena: self.arena.clone(),
+            span: self.back..self.front,
+            source_id: self.source_id,
         }
     }
+}
 
-    pub fn is_at_end(&self) -> bool {
-        self.current.end() >= self.rel_bounds()
+impl Cursor<char, SourceView> for SourceCursor {
+    fn peek_n(&mut self, i: usize) -> Option<char> {
+        self.get(self.front + i)
     }
 
-    /// look ahead in iterator without advancing
-    pub fn peek(&self) -> Option<char> {
-        if self.is_at_end() {
-            None
-        } else {
-            Some(
-                self.current
-                    .arena
-                    .upgrade()
-                    .unwrap()
-                    .get(self.current.end())
-                    .unwrap(),
-            )
-        }
+    fn previous(&self) -> Option<char> {
+        self.front.checked_sub(1).and_then(|i| self.get(i))
     }
 
-    pub fn peek_n(&self, n: usize) -> Option<char> {
-        if self.current.end() + n >= self.rel_bounds() {
-            None
-        } else {
-            Some(
-                self.current
-                    .arena
-                    .upgrade()
-                    .unwrap()
-                    .get(self.current.end() + n)
-                    .unwrap(),
-            )
-        }
+    fn peek_back_n(&self, i: usize) -> Option<char> {
+        self.get(self.back + i)
     }
 
-    pub fn advance_head(&mut self) -> Option<char> {
-        let ret = self.peek()?;
-        self.current.grow();
-        Some(ret)
+    fn window_len(&self) -> usize {
+        self.front - self.back
     }
 
-    pub fn advance_tail(&mut self) -> SourceView {
-        let ret = self.current.clone();
-        self.current.pull_tail();
-        ret
+    fn window_is_empty(&self) -> bool {
+        self.front == self.back
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let ret = self.get(self.front)?;
+        self.front += 1;
+        Some(ret)
     }
-}
 
-impl Iterator for Reader {
-    type Item = char;
+    fn consume(&mut self) -> Option<SourceView> {
+        let ret = SourceView {
+            arena: self.arena.clone(),
+            span: self.back..self.front,
+            source_id: self.source_id,
+        };
+        self.back = self.front;
+        Some(ret)
+    }
 
-    /// get next char, ignores the length of the window and leaves it unchanged
-    fn next(&mut self) -> Option<Self::Item> {
-        self.advance_head()
+    fn reset(&mut self) {
+        self.front = self.back;
     }
 }
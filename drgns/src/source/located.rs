@@ -0,0 +1,48 @@
+use std::fmt::{Debug, Display};
+
+use super::view::SourceView;
+use super::arena::FileRef;
+
+/// Attaches a source location to an arbitrary payload.
+///
+/// `Token.lexeme` is already a [`SourceView`], which carries its own
+/// location and [`FileRef`] -- the lexed text *is* the span, so `Token`
+/// doesn't need this wrapper. `Located<T>` is for everything that will
+/// eventually need a location but isn't itself a slice of source text, e.g.
+/// a parsed literal `Value` or an AST node built from several tokens.
+#[derive(Clone)]
+pub struct Located<T> {
+    pub item: T,
+    pub loc: SourceView,
+}
+
+impl<T> Located<T> {
+    pub fn new(item: T, loc: SourceView) -> Self {
+        Self { item, loc }
+    }
+
+    /// The file this item's location came from.
+    pub fn file(&self) -> FileRef {
+        self.loc.file()
+    }
+
+    /// Discard the location, keeping only the payload.
+    pub fn into_inner(self) -> T {
+        self.item
+    }
+}
+
+impl<T: Debug> Debug for Located<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Located")
+            .field("item", &self.item)
+            .field("loc", &self.loc)
+            .finish()
+    }
+}
+
+impl<T: Display> Display for Located<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.item, f)
+    }
+}
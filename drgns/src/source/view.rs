@@ -4,7 +4,7 @@ use std::rc::{Rc, Weak};
 
 use crate::assert_pre_condition;
 
-use super::arena::SourceArena;
+use super::arena::{FileRef, SourceArena};
 
 #[derive(Clone)]
 pub struct SourceView {
@@ -18,7 +18,7 @@ impl SourceView {
         Self {
             arena: Rc::downgrade(arena),
             span: 0..arena.len(),
-            source_id: todo!(),
+            source_id: 0,
         }
     }
 
@@ -43,6 +43,11 @@ impl SourceView {
         self.span.end
     }
 
+    /// The file (or REPL buffer) this view points into.
+    pub fn file(&self) -> FileRef {
+        FileRef(self.source_id)
+    }
+
     pub fn into_string(self) -> String {
         self.arena
             .upgrade()
@@ -1,65 +1,452 @@
+/// How integer arithmetic should behave when a result doesn't fit in an
+/// `i64`. `ExpressionEval` is configured with one of these, so the REPL and
+/// a future `build`/`check` mode can disagree about what overflow means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// overflow is a recoverable runtime error, reported via the error handler
+    #[default]
+    Checked,
+    /// overflow wraps around modularly, e.g. for tape/byte-style programs
+    Wrapping,
+    /// overflow clamps to the representable min/max
+    Saturating,
+}
+
+/// the ways in which `Value` arithmetic can fail at run time
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RuntimeError {
+    #[error("division by zero")]
+    DivideByZero,
+    /// only possible under [`OverflowPolicy::Checked`]; `Wrapping` and
+    /// `Saturating` always succeed by definition
+    #[error("integer overflow")]
+    IntegerOverflow,
+    /// `i64::pow` only accepts a `u32` exponent, so e.g. `2 ** -1` has no
+    /// integer result, unlike float exponentiation which handles negative
+    /// exponents just fine
+    #[error("exponent must not be negative")]
+    NegativeExponent,
+    #[error("cannot apply '{op}' to {lhs} and {rhs}")]
+    TypeMismatch {
+        op: &'static str,
+        lhs: &'static str,
+        rhs: &'static str,
+    },
+    /// a `List` index that falls outside `0..len`, including any negative
+    /// index
+    #[error("index out of bounds: length is {len} but index was {index}")]
+    IndexOutOfBounds { len: usize, index: i64 },
+}
+
 #[derive(Debug, Clone, derive_more::Display)]
 pub enum Value {
     Int(i64),
+
+    Float(f64),
+
+    Bool(bool),
+
+    #[display(
+        fmt = "{}{}{}i",
+        "re",
+        "if *im < 0.0 { \"-\" } else { \"+\" }",
+        "im.abs()"
+    )]
+    Complex { re: f64, im: f64 },
+
+    #[display(fmt = "[{}]", "_0.iter().map(ToString::to_string).collect::<Vec<_>>().join(\", \")")]
+    List(Vec<Value>),
 }
 
 impl Value {
-    pub fn neg(self) -> Option<Value> {
+    pub fn list(items: Vec<Value>) -> Value {
+        Value::List(items)
+    }
+
+    fn type_name(&self) -> &'static str {
         match self {
-            Value::Int(i) => Some(Value::Int(-i)),
-            _ => todo!("type error handling"),
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Bool(_) => "bool",
+            Value::Complex { .. } => "complex",
+            Value::List(_) => "list",
         }
     }
 
-    pub fn pow(self, rhs: Value) -> Option<Value> {
+    /// whether this value can take part in int/float/complex promotion
+    fn is_numeric(&self) -> bool {
+        matches!(self, Value::Int(_) | Value::Float(_) | Value::Complex { .. })
+    }
+
+    /// widen an `Int`/`Float`/`Complex` to its `(re, im)` pair, for the
+    /// mixed-type promotion arm of each arithmetic op; only meaningful when
+    /// `is_numeric` is true
+    fn to_complex(&self) -> (f64, f64) {
+        match *self {
+            Value::Int(i) => (i as f64, 0.0),
+            Value::Float(f) => (f, 0.0),
+            Value::Complex { re, im } => (re, im),
+            _ => unreachable!("to_complex called on a non-numeric Value"),
+        }
+    }
+
+    pub fn len(&self) -> Result<Value, RuntimeError> {
+        match self {
+            Value::List(xs) => Ok(Value::Int(xs.len() as i64)),
+            v => Err(RuntimeError::TypeMismatch {
+                op: "len",
+                lhs: v.type_name(),
+                rhs: v.type_name(),
+            }),
+        }
+    }
+
+    /// index into a list, returning [`RuntimeError::IndexOutOfBounds`] for
+    /// an index outside `0..len` and [`RuntimeError::TypeMismatch`] for
+    /// anything that isn't a `List`/`Int` pair
+    pub fn index(self, idx: Value) -> Result<Value, RuntimeError> {
+        match (self, idx) {
+            (Value::List(xs), Value::Int(i)) => {
+                let len = xs.len();
+                usize::try_from(i)
+                    .ok()
+                    .and_then(|i| xs.get(i).cloned())
+                    .ok_or(RuntimeError::IndexOutOfBounds { len, index: i })
+            }
+            (lhs, rhs) => Err(RuntimeError::TypeMismatch {
+                op: "[]",
+                lhs: lhs.type_name(),
+                rhs: rhs.type_name(),
+            }),
+        }
+    }
+
+    /// replace the element at `idx`, returning the updated list
+    pub fn index_assign(self, idx: Value, value: Value) -> Result<Value, RuntimeError> {
+        match (self, idx) {
+            (Value::List(mut xs), Value::Int(i)) => {
+                let len = xs.len();
+                match usize::try_from(i).ok().and_then(|i| xs.get_mut(i)) {
+                    Some(slot) => {
+                        *slot = value;
+                        Ok(Value::List(xs))
+                    }
+                    None => Err(RuntimeError::IndexOutOfBounds { len, index: i }),
+                }
+            }
+            (lhs, rhs) => Err(RuntimeError::TypeMismatch {
+                op: "[]=",
+                lhs: lhs.type_name(),
+                rhs: rhs.type_name(),
+            }),
+        }
+    }
+
+    pub fn neg(self, policy: OverflowPolicy) -> Result<Value, RuntimeError> {
+        match self {
+            Value::Int(i) => match policy {
+                OverflowPolicy::Checked => i
+                    .checked_neg()
+                    .map(Value::Int)
+                    .ok_or(RuntimeError::IntegerOverflow),
+                OverflowPolicy::Wrapping => Ok(Value::Int(i.wrapping_neg())),
+                OverflowPolicy::Saturating => Ok(Value::Int(i.saturating_neg())),
+            },
+            // floats never overflow under any policy, they saturate to +-inf
+            // the same way the hardware does
+            Value::Float(f) => Ok(Value::Float(-f)),
+            Value::Complex { re, im } => Ok(Value::Complex { re: -re, im: -im }),
+            v => Err(RuntimeError::TypeMismatch {
+                op: "neg",
+                lhs: v.type_name(),
+                rhs: v.type_name(),
+            }),
+        }
+    }
+
+    /// `!`: logical negation. Only defined for `Bool`, unlike `neg`'s
+    /// numeric negation.
+    pub fn not(self) -> Result<Value, RuntimeError> {
+        match self {
+            Value::Bool(b) => Ok(Value::Bool(!b)),
+            v => Err(RuntimeError::TypeMismatch {
+                op: "!",
+                lhs: v.type_name(),
+                rhs: v.type_name(),
+            }),
+        }
+    }
+
+    /// Coerce to a plain `bool`, for `&&`/`||` to branch on without the
+    /// evaluator having to know `Value`'s internal shape.
+    pub fn truthy(&self) -> Result<bool, RuntimeError> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            v => Err(RuntimeError::TypeMismatch {
+                op: "&&/||",
+                lhs: v.type_name(),
+                rhs: v.type_name(),
+            }),
+        }
+    }
+
+    /// Structural equality, with the same `Int`/`Float` cross-coercion the
+    /// arithmetic ops use (so `1 == 1.0`). Unlike arithmetic, mismatched
+    /// types compare unequal instead of erroring -- `1 == [1]` is a
+    /// meaningful (false) question, not a type error.
+    pub fn eq(&self, rhs: &Value) -> bool {
+        match (self, rhs) {
+            (Value::Int(x), Value::Int(y)) => x == y,
+            (Value::Float(x), Value::Float(y)) => x == y,
+            (Value::Int(x), Value::Float(y)) | (Value::Float(y), Value::Int(x)) => {
+                *x as f64 == *y
+            }
+            (Value::Bool(x), Value::Bool(y)) => x == y,
+            (Value::Complex { re: a, im: b }, Value::Complex { re: c, im: d }) => {
+                a == c && b == d
+            }
+            (Value::List(x), Value::List(y)) => {
+                x.len() == y.len() && x.iter().zip(y).all(|(a, b)| a.eq(b))
+            }
+            _ => false,
+        }
+    }
+
+    pub fn ne(&self, rhs: &Value) -> bool {
+        !self.eq(rhs)
+    }
+
+    /// Shared plumbing for `<`, `<=`, `>`, `>=`: numeric ordering, with the
+    /// same `Int`/`Float` coercion as the arithmetic ops. `op` is only used
+    /// to build the `TypeMismatch` message. A `None` ordering (e.g. a NaN
+    /// operand) makes every comparison false, matching IEEE 754.
+    fn compare(
+        self,
+        rhs: Value,
+        op: &'static str,
+        f: impl FnOnce(Option<std::cmp::Ordering>) -> bool,
+    ) -> Result<Value, RuntimeError> {
+        let ord = match (&self, &rhs) {
+            (Value::Int(x), Value::Int(y)) => Some(x.cmp(y)),
+            (Value::Float(x), Value::Float(y)) => x.partial_cmp(y),
+            (Value::Int(x), Value::Float(y)) => (*x as f64).partial_cmp(y),
+            (Value::Float(x), Value::Int(y)) => x.partial_cmp(&(*y as f64)),
+            _ => {
+                return Err(RuntimeError::TypeMismatch {
+                    op,
+                    lhs: self.type_name(),
+                    rhs: rhs.type_name(),
+                })
+            }
+        };
+        Ok(Value::Bool(f(ord)))
+    }
+
+    pub fn lt(self, rhs: Value) -> Result<Value, RuntimeError> {
+        self.compare(rhs, "<", |o| o.is_some_and(std::cmp::Ordering::is_lt))
+    }
+
+    pub fn le(self, rhs: Value) -> Result<Value, RuntimeError> {
+        self.compare(rhs, "<=", |o| o.is_some_and(std::cmp::Ordering::is_le))
+    }
+
+    pub fn gt(self, rhs: Value) -> Result<Value, RuntimeError> {
+        self.compare(rhs, ">", |o| o.is_some_and(std::cmp::Ordering::is_gt))
+    }
+
+    pub fn ge(self, rhs: Value) -> Result<Value, RuntimeError> {
+        self.compare(rhs, ">=", |o| o.is_some_and(std::cmp::Ordering::is_ge))
+    }
+
+    pub fn pow(self, rhs: Value, policy: OverflowPolicy) -> Result<Value, RuntimeError> {
         match (self, rhs) {
-            (Value::Int(x), Value::Int(y)) => Some(Value::Int(x.pow(y as u32))),
-            _ => todo!("type error handling"),
+            (Value::Int(x), Value::Int(y)) => {
+                let y: u32 = y.try_into().map_err(|_| RuntimeError::NegativeExponent)?;
+                match policy {
+                    OverflowPolicy::Checked => x
+                        .checked_pow(y)
+                        .map(Value::Int)
+                        .ok_or(RuntimeError::IntegerOverflow),
+                    OverflowPolicy::Wrapping => Ok(Value::Int(x.wrapping_pow(y))),
+                    OverflowPolicy::Saturating => Ok(Value::Int(x.saturating_pow(y))),
+                }
+            }
+            (Value::Float(x), Value::Float(y)) => Ok(Value::Float(x.powf(y))),
+            (Value::Int(x), Value::Float(y)) => Ok(Value::Float((x as f64).powf(y))),
+            (Value::Float(x), Value::Int(y)) => Ok(Value::Float(x.powi(y as i32))),
+            // a**b = exp(b * ln(a)), the standard definition of complex
+            // exponentiation; covers every int/float/complex combination
+            // where at least one side is complex
+            (lhs, rhs) if lhs.is_numeric() && rhs.is_numeric() => {
+                let (re, im) = c_exp(c_mul(rhs.to_complex(), c_ln(lhs.to_complex())));
+                Ok(Value::Complex { re, im })
+            }
+            (lhs, rhs) => Err(RuntimeError::TypeMismatch {
+                op: "**",
+                lhs: lhs.type_name(),
+                rhs: rhs.type_name(),
+            }),
         }
     }
 
-    pub fn mul(self, rhs: Value) -> Option<Value> {
+    pub fn mul(self, rhs: Value, policy: OverflowPolicy) -> Result<Value, RuntimeError> {
         match (self, rhs) {
-            (Value::Int(x), Value::Int(y)) => Some(Value::Int(x * y)),
-            _ => todo!("type error handling"),
+            (Value::Int(x), Value::Int(y)) => match policy {
+                OverflowPolicy::Checked => x
+                    .checked_mul(y)
+                    .map(Value::Int)
+                    .ok_or(RuntimeError::IntegerOverflow),
+                OverflowPolicy::Wrapping => Ok(Value::Int(x.wrapping_mul(y))),
+                OverflowPolicy::Saturating => Ok(Value::Int(x.saturating_mul(y))),
+            },
+            (Value::Float(x), Value::Float(y)) => Ok(Value::Float(x * y)),
+            (Value::Int(x), Value::Float(y)) => Ok(Value::Float(x as f64 * y)),
+            (Value::Float(x), Value::Int(y)) => Ok(Value::Float(x * y as f64)),
+            (lhs, rhs) if lhs.is_numeric() && rhs.is_numeric() => {
+                let (re, im) = c_mul(lhs.to_complex(), rhs.to_complex());
+                Ok(Value::Complex { re, im })
+            }
+            (lhs, rhs) => Err(RuntimeError::TypeMismatch {
+                op: "*",
+                lhs: lhs.type_name(),
+                rhs: rhs.type_name(),
+            }),
         }
     }
 
-    pub fn div(self, rhs: Value) -> Option<Value> {
+    pub fn div(self, rhs: Value, policy: OverflowPolicy) -> Result<Value, RuntimeError> {
         match (self, rhs) {
             (Value::Int(x), Value::Int(y)) => {
                 if y == 0 {
-                    todo!("handle runtime errors");
+                    return Err(RuntimeError::DivideByZero);
+                }
+                match policy {
+                    OverflowPolicy::Checked => x
+                        .checked_div(y)
+                        .map(Value::Int)
+                        .ok_or(RuntimeError::IntegerOverflow),
+                    OverflowPolicy::Wrapping => Ok(Value::Int(x.wrapping_div(y))),
+                    OverflowPolicy::Saturating => Ok(Value::Int(x.saturating_div(y))),
+                }
+            }
+            // unlike ints, a float division by zero is not an overflow, it's
+            // a well-defined IEEE 754 infinity or NaN
+            (Value::Float(x), Value::Float(y)) => Ok(Value::Float(x / y)),
+            (Value::Int(x), Value::Float(y)) => Ok(Value::Float(x as f64 / y)),
+            (Value::Float(x), Value::Int(y)) => Ok(Value::Float(x / y as f64)),
+            (lhs, rhs) if lhs.is_numeric() && rhs.is_numeric() => {
+                let (a, b) = lhs.to_complex();
+                let (c, d) = rhs.to_complex();
+                let denom = c * c + d * d;
+                if denom == 0.0 {
+                    return Err(RuntimeError::DivideByZero);
                 }
-                Some(Value::Int(x / y))
+                Ok(Value::Complex {
+                    re: (a * c + b * d) / denom,
+                    im: (b * c - a * d) / denom,
+                })
             }
-            _ => todo!("type error handling"),
+            (lhs, rhs) => Err(RuntimeError::TypeMismatch {
+                op: "/",
+                lhs: lhs.type_name(),
+                rhs: rhs.type_name(),
+            }),
         }
     }
 
-    pub fn rem(self, rhs: Value) -> Option<Value> {
+    pub fn rem(self, rhs: Value, policy: OverflowPolicy) -> Result<Value, RuntimeError> {
         match (self, rhs) {
             (Value::Int(x), Value::Int(y)) => {
                 if y == 0 {
-                    todo!("handle runtime errors");
+                    return Err(RuntimeError::DivideByZero);
+                }
+                match policy {
+                    OverflowPolicy::Checked => x
+                        .checked_rem(y)
+                        .map(Value::Int)
+                        .ok_or(RuntimeError::IntegerOverflow),
+                    OverflowPolicy::Wrapping => Ok(Value::Int(x.wrapping_rem(y))),
+                    // there's no meaningful way to "saturate" a remainder, so
+                    // fall back to the same wrapping behavior as `Wrapping`
+                    OverflowPolicy::Saturating => Ok(Value::Int(x.wrapping_rem(y))),
                 }
-                Some(Value::Int(x % y))
             }
-            _ => todo!("type error handling"),
+            (lhs, rhs) => Err(RuntimeError::TypeMismatch {
+                op: "%",
+                lhs: lhs.type_name(),
+                rhs: rhs.type_name(),
+            }),
         }
     }
 
-    pub fn add(self, rhs: Value) -> Option<Value> {
+    pub fn add(self, rhs: Value, policy: OverflowPolicy) -> Result<Value, RuntimeError> {
         match (self, rhs) {
-            (Value::Int(x), Value::Int(y)) => Some(Value::Int(x + y)),
-            _ => todo!("type error handling"),
+            (Value::Int(x), Value::Int(y)) => match policy {
+                OverflowPolicy::Checked => x
+                    .checked_add(y)
+                    .map(Value::Int)
+                    .ok_or(RuntimeError::IntegerOverflow),
+                OverflowPolicy::Wrapping => Ok(Value::Int(x.wrapping_add(y))),
+                OverflowPolicy::Saturating => Ok(Value::Int(x.saturating_add(y))),
+            },
+            (Value::Float(x), Value::Float(y)) => Ok(Value::Float(x + y)),
+            (Value::Int(x), Value::Float(y)) => Ok(Value::Float(x as f64 + y)),
+            (Value::Float(x), Value::Int(y)) => Ok(Value::Float(x + y as f64)),
+            (lhs, rhs) if lhs.is_numeric() && rhs.is_numeric() => {
+                let (a, b) = lhs.to_complex();
+                let (c, d) = rhs.to_complex();
+                Ok(Value::Complex { re: a + c, im: b + d })
+            }
+            (lhs, rhs) => Err(RuntimeError::TypeMismatch {
+                op: "+",
+                lhs: lhs.type_name(),
+                rhs: rhs.type_name(),
+            }),
         }
     }
 
-    pub fn sub(self, rhs: Value) -> Option<Value> {
+    pub fn sub(self, rhs: Value, policy: OverflowPolicy) -> Result<Value, RuntimeError> {
         match (self, rhs) {
-            (Value::Int(x), Value::Int(y)) => Some(Value::Int(x - y)),
-            _ => todo!("type error handling"),
+            (Value::Int(x), Value::Int(y)) => match policy {
+                OverflowPolicy::Checked => x
+                    .checked_sub(y)
+                    .map(Value::Int)
+                    .ok_or(RuntimeError::IntegerOverflow),
+                OverflowPolicy::Wrapping => Ok(Value::Int(x.wrapping_sub(y))),
+                OverflowPolicy::Saturating => Ok(Value::Int(x.saturating_sub(y))),
+            },
+            (Value::Float(x), Value::Float(y)) => Ok(Value::Float(x - y)),
+            (Value::Int(x), Value::Float(y)) => Ok(Value::Float(x as f64 - y)),
+            (Value::Float(x), Value::Int(y)) => Ok(Value::Float(x - y as f64)),
+            (lhs, rhs) if lhs.is_numeric() && rhs.is_numeric() => {
+                let (a, b) = lhs.to_complex();
+                let (c, d) = rhs.to_complex();
+                Ok(Value::Complex { re: a - c, im: b - d })
+            }
+            (lhs, rhs) => Err(RuntimeError::TypeMismatch {
+                op: "-",
+                lhs: lhs.type_name(),
+                rhs: rhs.type_name(),
+            }),
         }
     }
 }
+
+/// `(a.0, a.1) * (b.0, b.1)`: complex multiplication, shared by `mul` and
+/// `pow`'s `exp(b * ln(a))`.
+fn c_mul(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+/// complex natural log: `ln(re, im) = (ln(|z|), arg(z))`
+fn c_ln(z: (f64, f64)) -> (f64, f64) {
+    let (re, im) = z;
+    (re.hypot(im).ln(), im.atan2(re))
+}
+
+/// complex exponential: `exp(re, im) = e^re * (cos(im) + i sin(im))`
+fn c_exp(z: (f64, f64)) -> (f64, f64) {
+    let (re, im) = z;
+    let mag = re.exp();
+    (mag * im.cos(), mag * im.sin())
+}